@@ -0,0 +1,45 @@
+/// The flavour of SQL that is parsed. The core grammar is the same for all of them, the dialect only turns extensions of one database on or off.
+/// 1. `Generic` – Accepts every extension this parser knows (also ones of databases that don't have their own dialect here, like Oracle's `MINUS`). This is the default, and what `parse` and `tokenize` use.
+/// 2. `Ansi` – Standard SQL only: no backtick identifiers and no `LIMIT offset, count`.
+/// 3. `MySql` – Allows the MySQL extensions: identifiers in backticks (`` `my table` ``), `LIMIT offset, count` and `GROUP BY ... WITH ROLLUP`.
+/// 4. `Postgres` – Like `Ansi`, but also allows the `value::type` cast and `RETURNING` after `INSERT`, `UPDATE` and `DELETE`. PostgreSQL quotes identifiers with double quotes and writes `LIMIT count OFFSET offset`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Dialect {
+    #[default]
+    Generic,
+    Ansi,
+    MySql,
+    Postgres,
+}
+
+impl Dialect {
+    // `my table` is a quoted identifier in MySQL, standard SQL doesn't use backticks at all
+    pub fn supports_backtick_identifiers(&self) -> bool {
+        matches!(self, Dialect::Generic | Dialect::MySql)
+    }
+
+    // price::INT is the PostgreSQL way of writing CAST(price AS INT)
+    pub fn supports_double_colon_cast(&self) -> bool {
+        matches!(self, Dialect::Generic | Dialect::Postgres)
+    }
+
+    // LIMIT 10, 5 means skip 10 rows and return 5 (the same as LIMIT 5 OFFSET 10), only MySQL writes it like this
+    pub fn supports_limit_comma(&self) -> bool {
+        matches!(self, Dialect::Generic | Dialect::MySql)
+    }
+
+    // GROUP BY a, b WITH ROLLUP also returns subtotal rows, standard SQL writes GROUP BY ROLLUP (a, b) instead
+    pub fn supports_with_rollup(&self) -> bool {
+        matches!(self, Dialect::Generic | Dialect::MySql)
+    }
+
+    // MINUS is Oracle's name for EXCEPT, none of the other dialects know it
+    pub fn supports_minus(&self) -> bool {
+        matches!(self, Dialect::Generic)
+    }
+
+    // INSERT/UPDATE/DELETE ... RETURNING id gives back the changed rows, a PostgreSQL extension that MySQL doesn't have
+    pub fn supports_returning(&self) -> bool {
+        matches!(self, Dialect::Generic | Dialect::Postgres)
+    }
+}
@@ -0,0 +1,195 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use crate::token::{Span, Token};
+
+/// Errors that can happen while turning the input string into tokens. They are separate from `ParseError`, because at this point there are no tokens yet, only characters.
+/// Every error has a `position`: the offset of the offending character in the input, counted in characters (not bytes) from 0. For unterminated strings and identifiers it is the position of the opening quote, for numbers the position of their first digit.
+/// 1. `UnexpectedCharacter` – A character that can't start any token in the place where it was found (like a single `!`).
+/// 2. `UnterminatedString` – A string literal whose closing quote is missing. `content` is everything that was read after the opening quote.
+/// 3. `UnterminatedIdentifier` – The same, but for quoted identifiers (`"name"` or `` `name` ``).
+/// 4. `InvalidNumber` – A sequence of digits that can't be turned into a number.
+/// 5. `IntegerTooLarge` – A valid integer that doesn't fit into `u64`, the offending digits are kept so the user can see which literal it was.
+/// 6. `UnterminatedComment` – A `/*` comment without the closing `*/`. The position is the one of the `/*`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TokenizeError {
+    UnexpectedCharacter { character: char, position: usize },
+    UnterminatedString { quote: char, content: String, position: usize },
+    UnterminatedIdentifier { quote: char, content: String, position: usize },
+    InvalidNumber { digits: String, position: usize },
+    IntegerTooLarge { digits: String, position: usize },
+    UnterminatedComment { position: usize },
+}
+
+/// Errors returned by the parser. Callers can match on these instead of reading the message, for example to tell a missing semicolon apart from a trailing comma.
+/// 1. `UnexpectedToken` – The parser expected something (described in `expected`) but found a different token.
+/// 2. `UnexpectedEof` – The same as `UnexpectedToken`, but the input ended before the expected part was found.
+/// 3. `TrailingComma` – A list ended with a comma, `before` is the token that came right after it.
+/// 4. `Tokenize` – The input couldn't even be tokenized. Only returned by functions that take the raw input string, like `parse`.
+/// 5. `InStatement` – Wraps an error from one statement of a longer script (see `parse_many`), `index` is the position of the failing statement, starting from 0.
+/// 6. `InvalidCheck` – A `CHECK` constraint whose expression can't be a condition, like `CHECK (5)`. `expression` is the offending expression written back as SQL.
+/// 7. `NestingTooDeep` – Expressions or subqueries are nested deeper than the parser allows (`max_depth` levels, see `Parser::with_max_depth`). This protects against running out of stack on inputs like thousands of parentheses.
+/// 8. `DuplicateColumn` – A `CREATE TABLE` defines two columns with the same name (names are compared without case, so `a` and `A` are the same). `column` is the name of the second one.
+/// 9. `NoColumns` – A `CREATE TABLE` without any column definition, like `CREATE TABLE t ();`. `table` is the name of the table.
+/// 10. `InvalidLength` – The length of a `VARCHAR(n)` or `CHAR(n)` is `0` or more than `max` (see `ParserOptions::max_char_length`). `type_name` is `VARCHAR` or `CHAR`.
+/// 11. `InvalidOrdinal` – An `ORDER BY` position (`ORDER BY 3`) that isn't a column of the `SELECT` list, which has `columns` columns.
+/// 12. `ChainedComparison` – A comparison whose operand is another comparison, like `a < b < c`. Only returned with `ParserOptions::strict_comparisons`. `expression` is the whole comparison written back as SQL.
+/// 13. `EmptyInput` – There is nothing to parse, the input is empty or only has whitespace and comments.
+/// 14. `EmptyStatement` – A semicolon without a statement before it, like `;` alone or the second one in `SELECT a FROM t;;`.
+/// 15. `MisplacedClause` – A clause of a `SELECT` written after a clause that has to follow it, like `WHERE` after `ORDER BY` (`clause` is `WHERE`, `after` is `ORDER BY`), or written twice (then both are the same).
+/// 16. `InvalidDecimal` – The precision or scale of `DECIMAL(p, s)` doesn't fit into `u32`, or the scale is larger than the precision.
+/// 17. `ValueCountMismatch` – A row of `INSERT ... VALUES` with a different number of values (`found`, `DEFAULT` counts as a value) than the others: `expected` is the number of listed columns, or the length of the first row when the columns aren't written. `row` is the position of the row, starting from 0.
+///
+/// The first three, `EmptyStatement` and `MisplacedClause` also have a `span`, the place of the offending token in the input (for `TrailingComma` the comma itself, for `UnexpectedEof` the end of the input, for `EmptyStatement` the semicolon, for `MisplacedClause` the first keyword of the clause). It is filled in by `Parser::parse` when the parser knows where its tokens are (see `Parser::with_spans`), otherwise it is `None`. Use `span()` to get it from any error.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParseError {
+    UnexpectedToken { expected: String, found: Token<'static>, span: Option<Span> },
+    UnexpectedEof { expected: String, span: Option<Span> },
+    TrailingComma { before: Token<'static>, span: Option<Span> },
+    Tokenize(TokenizeError),
+    InStatement { index: usize, error: Box<ParseError> },
+    InvalidCheck { expression: String },
+    NestingTooDeep { max_depth: usize },
+    DuplicateColumn { column: String },
+    NoColumns { table: String },
+    InvalidLength { type_name: String, length: u64, max: Option<usize> },
+    InvalidOrdinal { ordinal: u64, columns: usize },
+    ChainedComparison { expression: String },
+    EmptyInput,
+    EmptyStatement { span: Option<Span> },
+    MisplacedClause { clause: String, after: String, span: Option<Span> },
+    InvalidDecimal { precision: u64, scale: Option<u64> },
+    ValueCountMismatch { row: usize, expected: usize, found: usize },
+}
+
+impl ParseError {
+    // Builds the right error for a token that isn't what we expected.
+    // If the "token" is just the end of the input, we report it as UnexpectedEof instead
+    pub fn unexpected(expected: impl Into<String>, found: &Token<'_>) -> Self {
+        match found {
+            Token::Eof => ParseError::UnexpectedEof { expected: expected.into(), span: None },
+            _ => ParseError::UnexpectedToken { expected: expected.into(), found: found.clone().into_owned(), span: None },
+        }
+    }
+
+    // A list that ends with a comma, `before` is the token after the comma (like ')' or FROM)
+    pub fn trailing_comma(before: &Token<'_>) -> Self {
+        ParseError::TrailingComma { before: before.clone().into_owned(), span: None }
+    }
+
+    // Where in the input the error is, if it is known. For an error inside a script (InStatement) it is the span of the inner error.
+    // Tokenizer errors have their own `position` instead, and the other errors aren't about a single token
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ParseError::UnexpectedToken { span, .. }
+            | ParseError::UnexpectedEof { span, .. }
+            | ParseError::TrailingComma { span, .. }
+            | ParseError::EmptyStatement { span }
+            | ParseError::MisplacedClause { span, .. } => *span,
+            ParseError::InStatement { error, .. } => error.span(),
+            _ => None,
+        }
+    }
+
+    // Sets the span of an error that is about one token, if it doesn't have one yet
+    pub(crate) fn set_span(&mut self, new_span: Span) {
+        match self {
+            ParseError::UnexpectedToken { span, .. }
+            | ParseError::UnexpectedEof { span, .. }
+            | ParseError::TrailingComma { span, .. }
+            | ParseError::EmptyStatement { span }
+            | ParseError::MisplacedClause { span, .. } => {
+                span.get_or_insert(new_span);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Display for TokenizeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenizeError::UnexpectedCharacter { character, position } => {
+                write!(f, "Unexpected character '{}' at position {}", character, position)
+            }
+            TokenizeError::UnterminatedString { quote, content, position } => {
+                write!(f, "Unterminated string starting with {}{} at position {}", quote, content, position)
+            }
+            TokenizeError::UnterminatedIdentifier { quote, content, position } => {
+                write!(f, "Unterminated quoted identifier starting with {}{} at position {}", quote, content, position)
+            }
+            TokenizeError::InvalidNumber { digits, position } => write!(f, "Invalid number {} at position {}", digits, position),
+            TokenizeError::IntegerTooLarge { digits, position } => {
+                write!(f, "Integer literal too large: {} at position {}", digits, position)
+            }
+            TokenizeError::UnterminatedComment { position } => write!(f, "Unterminated /* comment at position {}", position),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::UnexpectedToken { expected, found, .. } => write!(f, "Expected {}, found {}", expected, found),
+            ParseError::UnexpectedEof { expected, .. } => write!(f, "Expected {}, found end of input", expected),
+            ParseError::TrailingComma { before, .. } => write!(f, "Trailing comma before {} is not allowed", before),
+            ParseError::Tokenize(err) => write!(f, "{}", err),
+            ParseError::InStatement { index, error } => write!(f, "Statement at index {}: {}", index, error),
+            ParseError::NestingTooDeep { max_depth } => {
+                write!(f, "Expression nesting too deep (more than {} levels)", max_depth)
+            }
+            ParseError::DuplicateColumn { column } => write!(f, "Column {} is defined more than once", column),
+            ParseError::NoColumns { table } => write!(f, "Table {} must have at least one column", table),
+            ParseError::InvalidLength { type_name, length, max } => match max {
+                Some(max) if *length > 0 => write!(f, "{} length {} is more than the maximum of {}", type_name, length, max),
+                _ => write!(f, "{} length must be at least 1", type_name),
+            },
+            ParseError::InvalidOrdinal { ordinal, columns } => {
+                write!(f, "ORDER BY position {} is not in the SELECT list, it has {} column(s)", ordinal, columns)
+            }
+            ParseError::ChainedComparison { expression } => write!(
+                f,
+                "Comparisons can't be chained, {} compares the result of another comparison (write a < b AND b < c instead of a < b < c)",
+                expression
+            ),
+            ParseError::EmptyInput => write!(f, "Empty input, there is no statement to parse"),
+            ParseError::EmptyStatement { .. } => write!(f, "Empty statement, found ';' without a statement before it"),
+            ParseError::MisplacedClause { clause, after, .. } if clause == after => write!(f, "{} can only be written once", clause),
+            ParseError::MisplacedClause { clause, after, .. } => write!(f, "{} must come before {}", clause, after),
+            // Only returned with a scale when the precision itself is fine
+            ParseError::InvalidDecimal { precision, scale } => {
+                let scale = scale.unwrap_or_default();
+                if *precision > u32::MAX as u64 {
+                    write!(f, "DECIMAL precision {} is more than the maximum of {}", precision, u32::MAX)
+                } else if scale > u32::MAX as u64 {
+                    write!(f, "DECIMAL scale {} is more than the maximum of {}", scale, u32::MAX)
+                } else {
+                    write!(f, "DECIMAL scale {} is larger than its precision {}", scale, precision)
+                }
+            }
+            ParseError::InvalidCheck { expression } => {
+                write!(f, "CHECK constraint must be a condition (like a comparison), found {}", expression)
+            }
+            ParseError::ValueCountMismatch { row, expected, found } => {
+                write!(f, "Row at index {} of VALUES has {} value(s), expected {}", row, found, expected)
+            }
+        }
+    }
+}
+
+impl Error for TokenizeError {}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Tokenize(err) => Some(err),
+            ParseError::InStatement { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<TokenizeError> for ParseError {
+    fn from(err: TokenizeError) -> Self {
+        ParseError::Tokenize(err)
+    }
+}
@@ -0,0 +1,258 @@
+use crate::statement::{Expression, Statement, TableColumn};
+use crate::tokenizer::keyword_for;
+
+/// Settings for `format_sql`, the pretty-printer. `Display` on a `Statement` always prints it on one line, this is for showing SQL to people.
+/// 1. `uppercase_keywords` – Write keywords as `SELECT`, `FROM`, ... (`true`) or as `select`, `from`, ... (`false`). Names and strings are never changed.
+/// 2. `indent_width` – How many spaces are used to indent the items of a list when they are written on separate lines.
+/// 3. `column_per_line` – Write every column of a `SELECT` (and every column definition of a `CREATE TABLE`) on its own line.
+///
+/// The default is uppercase keywords, 4 spaces and one column per line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatOptions {
+    pub uppercase_keywords: bool,
+    pub indent_width: usize,
+    pub column_per_line: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions { uppercase_keywords: true, indent_width: 4, column_per_line: true }
+    }
+}
+
+// Formats a statement over several lines, every clause (FROM, WHERE, GROUP BY, ORDER BY, LIMIT) starts on a new line.
+// For example, with the default options, SELECT id, name FROM users WHERE age > 18; becomes:
+// SELECT
+//     id,
+//     name
+// FROM users
+// WHERE age > 18;
+// Expressions themselves are written by their Display implementation, so the result can always be parsed back
+pub fn format_sql(stmt: &Statement, opts: FormatOptions) -> String {
+    let indent = " ".repeat(opts.indent_width);
+    let sql = format_statement(stmt, &opts, &indent);
+    apply_keyword_case(&sql, opts.uppercase_keywords)
+}
+
+fn format_statement(stmt: &Statement, opts: &FormatOptions, indent: &str) -> String {
+    match stmt {
+        Statement::Select { .. } | Statement::SetOperation { .. } => format!("{};", format_query(stmt, opts, indent)),
+        Statement::CreateTable { table_name, column_list, table_constraints, if_not_exists } => {
+            let create = create_table(*if_not_exists);
+            let mut items = format_columns(column_list, opts.column_per_line);
+            items.extend(table_constraints.iter().map(|constraint| constraint.to_string()));
+            if opts.column_per_line && !items.is_empty() {
+                let lines: Vec<String> = items.iter().map(|item| format!("{}{}", indent, item)).collect();
+                format!("{} {} (\n{}\n);", create, table_name, lines.join(",\n"))
+            } else {
+                format!("{} {} ({});", create, table_name, items.join(", "))
+            }
+        }
+        Statement::CreateTableAs { table_name, if_not_exists, query } => {
+            format!("{} {} AS\n{}", create_table(*if_not_exists), table_name, format_statement(query, opts, indent))
+        }
+        Statement::Explain { analyze, inner } => {
+            format!("EXPLAIN{}\n{}", if *analyze { " ANALYZE" } else { "" }, format_statement(inner, opts, indent))
+        }
+        // Like in a SELECT, every clause starts on a new line, and the rows, the assignments and the RETURNING columns are lists
+        Statement::Insert { table_name, columns, values, returning } => {
+            let mut sql = format!("INSERT INTO {}", table_name);
+            if !columns.is_empty() {
+                let columns: Vec<String> = columns.iter().map(|column| name(column)).collect();
+                sql.push_str(&format!(" ({})", columns.join(", ")));
+            }
+            let rows: Vec<String> = values.iter().map(|row| {
+                let row: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+                format!("({})", row.join(", "))
+            }).collect();
+            sql.push_str("\nVALUES");
+            sql.push_str(&format_list(&rows, opts, indent));
+            format!("{}{};", sql, format_returning(returning, opts, indent))
+        }
+        Statement::Update { table_name, assignments, r#where, returning } => {
+            let assignments: Vec<String> = assignments.iter().map(|assignment| assignment.to_string()).collect();
+            let mut sql = format!("UPDATE {}\nSET{}", table_name, format_list(&assignments, opts, indent));
+            if let Some(expr) = r#where {
+                sql.push_str(&format!("\nWHERE {}", expr));
+            }
+            format!("{}{};", sql, format_returning(returning, opts, indent))
+        }
+        Statement::Delete { table_name, r#where, returning } => {
+            let mut sql = format!("DELETE FROM {}", table_name);
+            if let Some(expr) = r#where {
+                sql.push_str(&format!("\nWHERE {}", expr));
+            }
+            format!("{}{};", sql, format_returning(returning, opts, indent))
+        }
+        // ALTER TABLE changes only one thing, TRUNCATE has only the table name and an index only a few columns, so they stay on one line
+        Statement::AlterTable { .. } | Statement::Truncate { .. } | Statement::CreateIndex { .. } => stmt.to_string(),
+    }
+}
+
+// Formats a query without the semicolon at the end. The operator of a set operation gets its own line between the two queries
+fn format_query(stmt: &Statement, opts: &FormatOptions, indent: &str) -> String {
+    match stmt {
+        Statement::Select { columns, from, r#where, orderby, ctes, limit, group_by, with_rollup } => {
+            let columns: Vec<String> = columns.iter().map(|column| column.to_string()).collect();
+            // Every query of a WITH clause gets its own line, the main SELECT starts after them
+            let mut sql = String::new();
+            if !ctes.is_empty() {
+                let ctes: Vec<String> = ctes.iter().map(|cte| cte.to_string()).collect();
+                sql.push_str(&format!("WITH {}\n", ctes.join(&format!(",\n{}", indent))));
+            }
+            sql.push_str("SELECT");
+            sql.push_str(&format_list(&columns, opts, indent));
+            sql.push_str(&format!("\nFROM {}", from));
+            if let Some(expr) = r#where {
+                sql.push_str(&format!("\nWHERE {}", expr));
+            }
+            if !group_by.is_empty() {
+                let items: Vec<String> = group_by.iter().map(|item| item.to_string()).collect();
+                sql.push_str(&format!("\nGROUP BY {}", items.join(", ")));
+                if *with_rollup {
+                    sql.push_str(" WITH ROLLUP");
+                }
+            }
+            if !orderby.is_empty() {
+                let items: Vec<String> = orderby.iter().map(|item| item.to_string()).collect();
+                sql.push_str(&format!("\nORDER BY {}", items.join(", ")));
+            }
+            if let Some(limit) = limit {
+                sql.push_str(&format!("\n{}", limit));
+            }
+            sql
+        }
+        Statement::SetOperation { op, all, left, right } => {
+            let all = if *all { " ALL" } else { "" };
+            format!("{}\n{}{}\n{}", format_query(left, opts, indent), op, all, format_query(right, opts, indent))
+        }
+        other => format_statement(other, opts, indent),
+    }
+}
+
+fn create_table(if_not_exists: bool) -> &'static str {
+    if if_not_exists {
+        "CREATE TABLE IF NOT EXISTS"
+    } else {
+        "CREATE TABLE"
+    }
+}
+
+// Writes the SELECT list (or another list, like the rows of VALUES) either on the same line as SELECT or one item per (indented) line
+fn format_list(items: &[String], opts: &FormatOptions, indent: &str) -> String {
+    if opts.column_per_line {
+        let lines: Vec<String> = items.iter().map(|item| format!("\n{}{}", indent, item)).collect();
+        lines.join(",")
+    } else {
+        format!(" {}", items.join(", "))
+    }
+}
+
+fn format_returning(returning: &[Expression], opts: &FormatOptions, indent: &str) -> String {
+    if returning.is_empty() {
+        return String::new();
+    }
+    let columns: Vec<String> = returning.iter().map(|column| column.to_string()).collect();
+    format!("\nRETURNING{}", format_list(&columns, opts, indent))
+}
+
+// When the column definitions are on separate lines, the types are aligned in one column:
+//     id      INT PRIMARY KEY,
+//     name    VARCHAR(50)
+fn format_columns(columns: &[TableColumn], align: bool) -> Vec<String> {
+    let names: Vec<String> = columns.iter().map(|column| name(&column.column_name)).collect();
+    let width = if align { names.iter().map(|n| n.chars().count()).max().unwrap_or(0) } else { 0 };
+    columns.iter().zip(names).map(|(column, column_name)| {
+        let mut item = format!("{:<width$} {}", column_name, column.column_type, width = width);
+        for constraint in &column.constraints {
+            item.push_str(&format!(" {}", constraint));
+        }
+        item
+    }).collect()
+}
+
+// A name is written the same way as in Display (in double quotes if it is a keyword or has special characters)
+fn name(name: &str) -> String {
+    Expression::Identifier(name.to_string()).to_string()
+}
+
+// Display always writes keywords in uppercase, so for lowercase output we change every word that is a keyword.
+// Strings and quoted names are copied as they are, and a name that looks like a keyword is always quoted,
+// so every bare word that is a keyword really is one
+fn apply_keyword_case(sql: &str, uppercase: bool) -> String {
+    if uppercase {
+        return sql.to_string();
+    }
+    let mut result = String::with_capacity(sql.len());
+    let mut chars = sql.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '\'' | '"' | '`' => {
+                result.push(c);
+                for (_, next) in chars.by_ref() {
+                    result.push(next);
+                    if next == c {
+                        break;
+                    }
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut end = sql.len();
+                while let Some(&(i, next)) = chars.peek() {
+                    if next.is_ascii_alphanumeric() || next == '_' {
+                        chars.next();
+                    } else {
+                        end = i;
+                        break;
+                    }
+                }
+                let word = &sql[start..end];
+                if keyword_for(word).is_some() {
+                    result.push_str(&word.to_lowercase());
+                } else {
+                    result.push_str(word);
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_sql, FormatOptions};
+    use crate::parse;
+
+    fn format(sql: &str, opts: FormatOptions) -> String {
+        format_sql(&parse(sql).unwrap(), opts)
+    }
+
+    #[test]
+    fn select_columns_are_indented_with_four_spaces() {
+        let sql = "SELECT id, name, age * 2 FROM users WHERE age > 18 ORDER BY name;";
+        assert_eq!(
+            format(sql, FormatOptions::default()),
+            "SELECT\n    id,\n    name,\n    age * 2\nFROM users\nWHERE age > 18\nORDER BY name;"
+        );
+        // The formatted text is still the same query
+        assert_eq!(parse(&format(sql, FormatOptions::default())), parse(sql));
+    }
+
+    #[test]
+    fn indent_width_and_keyword_case_are_options() {
+        let opts = FormatOptions { uppercase_keywords: false, indent_width: 2, column_per_line: true };
+        assert_eq!(format("SELECT id, name FROM users;", opts), "select\n  id,\n  name\nfrom users;");
+        let opts = FormatOptions { column_per_line: false, ..FormatOptions::default() };
+        assert_eq!(format("SELECT id, name FROM users;", opts), "SELECT id, name\nFROM users;");
+    }
+
+    #[test]
+    fn insert_rows_are_a_list() {
+        let sql = "INSERT INTO t (a, b) VALUES (1, DEFAULT), (DEFAULT, 2);";
+        assert_eq!(format(sql, FormatOptions::default()), "INSERT INTO t (a, b)\nVALUES\n    (1, DEFAULT),\n    (DEFAULT, 2);");
+        let opts = FormatOptions { uppercase_keywords: false, ..FormatOptions::default() };
+        assert_eq!(format(sql, opts), "insert into t (a, b)\nvalues\n    (1, default),\n    (default, 2);");
+        assert_eq!(parse(&format(sql, FormatOptions::default())), parse(sql));
+    }
+}
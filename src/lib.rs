@@ -0,0 +1,147 @@
+//! A small SQL parser for `SELECT` and `CREATE TABLE` statements.
+//!
+//! The simplest way to use it is the `parse` function, which tokenizes and parses one statement:
+//! ```
+//! let statement = rust_sql_parser::parse("SELECT name FROM users;").unwrap();
+//! ```
+//! Scripts with several statements can be parsed at once with `parse_many` (or `parse_many_recover`, which keeps going after a broken statement,
+//! or `parse_spans`, which also gives the part of the input every statement came from).
+//! The tokenizer and the parser can also be used separately through `tokenize` and `Parser`.
+//! Parsed statements can be printed back as SQL with `Display` (on one line) or `format_sql` (pretty-printed).
+
+use std::ops::Range;
+use std::str::FromStr;
+
+pub mod token;
+pub mod tokenizer;
+pub mod sql_parser;
+pub mod statement;
+pub mod pratt_parsing;
+pub mod error;
+pub mod format;
+pub mod visitor;
+pub mod optimize;
+pub mod warning;
+pub mod dialect;
+pub mod summary;
+
+#[allow(non_snake_case)]
+mod ForBonusPoints;
+
+pub use crate::dialect::Dialect;
+pub use crate::error::{ParseError, TokenizeError};
+pub use crate::format::{format_sql, FormatOptions};
+pub use crate::optimize::{flatten_logical, fold_constants};
+pub use crate::pratt_parsing::{get_precedence, precedence_of};
+pub use crate::sql_parser::{Parser, ParserOptions, DEFAULT_MAX_CHAR_LENGTH, DEFAULT_MAX_DEPTH, DEFAULT_NON_RESERVED_KEYWORDS};
+pub use crate::statement::{AlterTableAction, Assignment, BinaryOperator, CommonTableExpression, Constraint, DBType, DropBehavior, Expression, FloatLiteral, IntervalUnit, Limit, NullsOrder, OrderByItem, OrderDirection, Quantifier, RefAction, SetOp, Statement, StatementKind, TableColumn, TableConstraint, TableName, UnaryOperator, WindowSpec};
+pub use crate::token::{Keyword, Placeholder, Span, Token};
+pub use crate::tokenizer::{tokenize, tokenize_debug, tokenize_with_comments, tokenize_with_spans, Tokenizer};
+pub use crate::visitor::{walk_expression, walk_statement, Visitor};
+pub use crate::summary::{summarize, Summary};
+pub use crate::warning::Warning;
+
+// Tokenizes and parses a single statement in one call
+// Tokenizer errors are turned into ParseError::Tokenize, so there is only one error type to handle
+// The semicolon at the end is optional here, because statements passed from code usually don't have one
+pub fn parse(input: &str) -> Result<Statement, ParseError> {
+    let tokens = tokenize_with_spans(input)?;
+    Parser::with_spans(tokens, lenient_options()).parse()
+}
+
+// let statement: Statement = "SELECT name FROM users".parse()?;
+// Like parse, but the input has to be exactly one statement: anything after its semicolon (except comments) is an error,
+// so a script isn't silently cut to its first statement. For scripts there is parse_many
+impl FromStr for Statement {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize_with_spans(input)?;
+        let mut parser = Parser::with_spans(tokens, lenient_options());
+        let statement = parser.parse()?;
+        if !parser.is_at_end() {
+            let mut error = ParseError::unexpected("end of input after the statement (use parse_many for several statements)", parser.peek());
+            if let Some(span) = parser.current_span() {
+                error.set_span(span);
+            }
+            return Err(error);
+        }
+        Ok(statement)
+    }
+}
+
+// The same as parse, but only accepts the extensions of the given dialect (see Dialect),
+// for example Dialect::Ansi rejects `backtick` identifiers and LIMIT 10, 5
+pub fn parse_with_dialect(input: &str, dialect: Dialect) -> Result<Statement, ParseError> {
+    let mut tokenizer = Tokenizer::new(input).with_dialect(dialect);
+    let tokens = std::iter::from_fn(|| tokenizer.next_spanned()).collect::<Result<Vec<_>, _>>()?;
+    Parser::with_spans(tokens, ParserOptions { dialect, ..lenient_options() }).parse()
+}
+
+// The same as parse, but also returns warnings about things that are valid but most likely mistakes,
+// like NOT NULL written twice on one column (see Warning). They are looked for only after parsing,
+// so parse itself doesn't get any slower because of them
+pub fn parse_with_warnings(input: &str) -> Result<(Statement, Vec<Warning>), ParseError> {
+    let statement = parse(input)?;
+    let warnings = warning::collect_warnings(&statement);
+    Ok((statement, warnings))
+}
+
+// Tokenizes the whole input once and parses every statement in it, one after another
+// Each statement ends with its own semicolon, like in a migration file or a REPL session
+// If one of them fails, the error says at which index (starting from 0) it happened
+// Like in parse, the last statement doesn't need a semicolon
+pub fn parse_many(input: &str) -> Result<Vec<Statement>, ParseError> {
+    let tokens = tokenize_with_spans(input)?;
+    let mut parser = Parser::with_spans(tokens, lenient_options());
+    let mut statements = Vec::new();
+    while !parser.is_at_end() {
+        let statement = parser.parse().map_err(|error| ParseError::InStatement {
+            index: statements.len(),
+            error: Box::new(error),
+        })?;
+        statements.push(statement);
+    }
+    Ok(statements)
+}
+
+// Like parse_many, but every statement comes with the bytes of the input it was parsed from
+// (from its first token up to and including its semicolon, if it has one), for tools that split a script
+// into statements. Comments between statements aren't part of any range. The error is the message of the first problem
+pub fn parse_spans(input: &str) -> Result<Vec<(Statement, Range<usize>)>, String> {
+    let tokens = tokenize_with_spans(input).map_err(|error| error.to_string())?;
+    let spans: Vec<Span> = tokens.iter().map(|(_, span)| *span).collect();
+    let mut parser = Parser::with_spans(tokens, lenient_options());
+    let mut statements = Vec::new();
+    while !parser.is_at_end() {
+        let first = parser.position();
+        let statement = parser
+            .parse()
+            .map_err(|error| ParseError::InStatement { index: statements.len(), error: Box::new(error) }.to_string())?;
+        // The statement consumed at least one token, the last one it consumed is right before the current position
+        let range = spans[first].start..spans[parser.position() - 1].end;
+        statements.push((statement, range));
+    }
+    Ok(statements)
+}
+
+// Like parse_many, but a broken statement doesn't stop the others from being parsed: parsing continues after
+// its semicolon, so every statement gets its own result (in a migration file one typo doesn't hide the rest).
+// If the input can't even be tokenized, there are no statements to look at, so the only result is that error
+pub fn parse_many_recover(input: &str) -> Vec<Result<Statement, ParseError>> {
+    let tokens = match tokenize_with_spans(input) {
+        Ok(tokens) => tokens,
+        Err(error) => return vec![Err(error.into())],
+    };
+    let mut parser = Parser::with_spans(tokens, lenient_options());
+    let mut results = Vec::new();
+    while !parser.is_at_end() {
+        results.push(parser.parse_and_recover());
+    }
+    results
+}
+
+// The library functions accept a missing semicolon after the last statement, the REPL still requires it
+fn lenient_options() -> ParserOptions {
+    ParserOptions { require_semicolon: false, ..ParserOptions::default() }
+}
@@ -1,15 +1,33 @@
 use std::io::{self, Write};
-use crate::tokenizer::tokenize;
-use crate::sql_parser::Parser;
-use crate::statement::Statement;
+use rust_sql_parser::{tokenize, tokenize_with_spans, Parser, ParserOptions, Token, TokenizeError};
 
-mod token;
-mod tokenizer;
-mod sql_parser;
-mod statement;
-mod pratt_parsing;
-
-mod ForBonusPoints;
+// Decides if the buffer holds whole statements that can be parsed. Looking at the text alone isn't enough:
+// a ';' inside a string (SELECT ';' FROM t) or inside unclosed parentheses doesn't end the statement.
+// So the buffer is tokenized, and it is complete when its last token is a semicolon outside of any parentheses.
+// If the tokenizer fails for another reason than an unfinished string, we say it is complete so the error gets shown
+fn is_complete_statement(buffer: &str) -> bool {
+    match tokenize(buffer) {
+        Ok(tokens) => {
+            let mut depth: i32 = 0;
+            let mut last_is_semicolon = false;
+            for token in tokens.iter().filter(|token| **token != Token::Eof) {
+                match token {
+                    Token::LeftParentheses => depth += 1,
+                    Token::RightParentheses => depth -= 1,
+                    _ => {}
+                }
+                last_is_semicolon = *token == Token::Semicolon && depth <= 0;
+            }
+            last_is_semicolon
+        }
+        Err(
+            TokenizeError::UnterminatedString { .. }
+            | TokenizeError::UnterminatedIdentifier { .. }
+            | TokenizeError::UnterminatedComment { .. },
+        ) => false,
+        Err(_) => true,
+    }
+}
 
 fn main() {
     println!("Please, enter your SQL queries to check my SQL Parser");
@@ -24,9 +42,14 @@ fn main() {
 
         // Read a line of user input
         let mut input = String::new();
-        if io::stdin().read_line(&mut input).is_err() {
-            println!("Failed to read input.");
-            continue;
+        match io::stdin().read_line(&mut input) {
+            // The input was closed (Ctrl+D or the end of a piped file), there is nothing more to read
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(_) => {
+                println!("Failed to read input.");
+                continue;
+            }
         }
         // Check if the user wants to exit
         let trimmed = input.trim();
@@ -35,22 +58,34 @@ fn main() {
             break;
         }
 
-        // Add the user's input to the buffer and make sure there's a space after it
+        // Add the user's input to the buffer and keep the line break after it,
+        // so a -- comment at the end of this line doesn't swallow the next lines
         buffer.push_str(input.trim_end());
-        buffer.push(' ');
+        buffer.push('\n');
 
         // Only parse when we detect the end of a full SQL statement marked by a semicolon
-        if buffer.trim_end().ends_with(';') {
-            match tokenize(&buffer) {
+        // Several statements can be written on one line, then each of them is parsed and printed
+        if is_complete_statement(&buffer) {
+            match tokenize_with_spans(&buffer) {
                 Ok(tokens) => {
-                    let mut parser = Parser::new(tokens);
-                    match parser.parse() {
-                        Ok(statement) => {
-                            // Pretty-print the successfully parsed SQL AST
-                            println!("PARSED SUCCESFULLY, here is:\n{:#?}", statement);
-                        }
-                        Err(err) => {
-                            println!("Parser error: {}", err);
+                    let mut parser = Parser::with_spans(tokens, ParserOptions::default());
+                    while !parser.is_at_end() {
+                        match parser.parse() {
+                            Ok(statement) => {
+                                // Pretty-print the successfully parsed SQL AST
+                                println!("PARSED SUCCESFULLY, here is:\n{:#?}", statement);
+                            }
+                            Err(err) => {
+                                // The span tells where the error is, so we can point at the line and column in the buffer
+                                match err.span() {
+                                    Some(span) => {
+                                        let (line, column) = span.line_and_column(&buffer);
+                                        println!("Parser error: {} (line {}, column {})", err, line, column);
+                                    }
+                                    None => println!("Parser error: {}", err),
+                                }
+                                break;
+                            }
                         }
                     }
                 }
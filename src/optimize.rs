@@ -0,0 +1,232 @@
+use crate::statement::{BinaryOperator, Expression, OrderByItem, UnaryOperator, WindowSpec};
+
+// Evaluates the parts of an expression that only use literal numbers and booleans, so WHERE 2 + 3 > x becomes WHERE 5 > x.
+// Anything that contains a name (or a string, NULL, ...) is left as it is, because its value is only known in the database.
+// Operations that can't be computed on u64 (like 2 - 3, -4 or a division by zero) are left unfolded instead of panicking
+pub fn fold_constants(expr: Expression) -> Expression {
+    match expr {
+        Expression::BinaryOperation { left_operand, operator, right_operand } => {
+            // The children are folded first, so (1 + 2) * 3 can be folded as a whole
+            let left = fold_constants(*left_operand);
+            let right = fold_constants(*right_operand);
+            match evaluate_binary(&left, &operator, &right) {
+                Some(value) => value,
+                None => Expression::BinaryOperation {
+                    left_operand: Box::new(left),
+                    operator,
+                    right_operand: Box::new(right),
+                },
+            }
+        }
+        Expression::UnaryOperation { operand, operator } => {
+            let operand = fold_constants(*operand);
+            match (&operator, operand) {
+                (UnaryOperator::Not, Expression::Bool(value)) => Expression::Bool(!value),
+                (UnaryOperator::Plus, Expression::Number(n)) => Expression::Number(n),
+                (_, operand) => Expression::UnaryOperation { operand: Box::new(operand), operator },
+            }
+        }
+        Expression::Tuple(elements) => Expression::Tuple(elements.into_iter().map(fold_constants).collect()),
+        // The function itself can't be evaluated here, but its arguments can
+        Expression::FunctionCall { name, args, distinct, window } => Expression::FunctionCall {
+            name,
+            args: args.into_iter().map(fold_constants).collect(),
+            distinct,
+            window: window.map(|window| map_window(window, fold_constants)),
+        },
+        Expression::InList { expr, list, negated } => Expression::InList {
+            expr: Box::new(fold_constants(*expr)),
+            list: list.into_iter().map(fold_constants).collect(),
+            negated,
+        },
+        Expression::Is { expr, value, negated } => Expression::Is { expr: Box::new(fold_constants(*expr)), value, negated },
+        Expression::QuantifiedComparison { expr, op, quantifier, query } => {
+            Expression::QuantifiedComparison { expr: Box::new(fold_constants(*expr)), op, quantifier, query }
+        }
+        Expression::Cast { expr, target } => Expression::Cast { expr: Box::new(fold_constants(*expr)), target },
+        Expression::Like { expr, pattern, negated, escape } => Expression::Like {
+            expr: Box::new(fold_constants(*expr)),
+            pattern: Box::new(fold_constants(*pattern)),
+            negated,
+            escape,
+        },
+        Expression::And(items) => Expression::And(items.into_iter().map(fold_constants).collect()),
+        Expression::Or(items) => Expression::Or(items.into_iter().map(fold_constants).collect()),
+        other => other,
+    }
+}
+
+// Turns chains of the same logical operator into one n-ary node: a AND b AND c (two nested BinaryOperations)
+// becomes Expression::And(vec![a, b, c]). Only the same operator is merged, so a AND (b OR c) keeps its OR inside.
+// Everything else is kept as it is, but its operands are flattened too (the queries of subqueries are not touched)
+pub fn flatten_logical(expr: Expression) -> Expression {
+    match expr {
+        Expression::BinaryOperation { left_operand, operator: BinaryOperator::And, right_operand } => {
+            Expression::And(flatten_chain(vec![*left_operand, *right_operand], &BinaryOperator::And))
+        }
+        Expression::BinaryOperation { left_operand, operator: BinaryOperator::Or, right_operand } => {
+            Expression::Or(flatten_chain(vec![*left_operand, *right_operand], &BinaryOperator::Or))
+        }
+        Expression::And(items) => Expression::And(flatten_chain(items, &BinaryOperator::And)),
+        Expression::Or(items) => Expression::Or(flatten_chain(items, &BinaryOperator::Or)),
+        Expression::BinaryOperation { left_operand, operator, right_operand } => Expression::BinaryOperation {
+            left_operand: Box::new(flatten_logical(*left_operand)),
+            operator,
+            right_operand: Box::new(flatten_logical(*right_operand)),
+        },
+        Expression::UnaryOperation { operand, operator } => {
+            Expression::UnaryOperation { operand: Box::new(flatten_logical(*operand)), operator }
+        }
+        Expression::Tuple(elements) => Expression::Tuple(elements.into_iter().map(flatten_logical).collect()),
+        Expression::FunctionCall { name, args, distinct, window } => Expression::FunctionCall {
+            name,
+            args: args.into_iter().map(flatten_logical).collect(),
+            distinct,
+            window: window.map(|window| map_window(window, flatten_logical)),
+        },
+        Expression::InList { expr, list, negated } => Expression::InList {
+            expr: Box::new(flatten_logical(*expr)),
+            list: list.into_iter().map(flatten_logical).collect(),
+            negated,
+        },
+        Expression::InSubquery { expr, query, negated } => {
+            Expression::InSubquery { expr: Box::new(flatten_logical(*expr)), query, negated }
+        }
+        Expression::Is { expr, value, negated } => Expression::Is { expr: Box::new(flatten_logical(*expr)), value, negated },
+        Expression::QuantifiedComparison { expr, op, quantifier, query } => {
+            Expression::QuantifiedComparison { expr: Box::new(flatten_logical(*expr)), op, quantifier, query }
+        }
+        Expression::Cast { expr, target } => Expression::Cast { expr: Box::new(flatten_logical(*expr)), target },
+        Expression::Like { expr, pattern, negated, escape } => Expression::Like {
+            expr: Box::new(flatten_logical(*expr)),
+            pattern: Box::new(flatten_logical(*pattern)),
+            negated,
+            escape,
+        },
+        other => other,
+    }
+}
+
+// Flattens every operand and takes the operands of the ones that use the same operator in their place
+fn flatten_chain(operands: Vec<Expression>, operator: &BinaryOperator) -> Vec<Expression> {
+    let mut items = Vec::new();
+    for operand in operands {
+        match (operator, flatten_logical(operand)) {
+            (BinaryOperator::And, Expression::And(inner)) | (BinaryOperator::Or, Expression::Or(inner)) => items.extend(inner),
+            (_, flattened) => items.push(flattened),
+        }
+    }
+    items
+}
+
+// Applies one of the transformations above to every expression of a window (OVER (PARTITION BY ... ORDER BY ...))
+fn map_window(window: WindowSpec, transform: fn(Expression) -> Expression) -> WindowSpec {
+    WindowSpec {
+        partition_by: window.partition_by.into_iter().map(transform).collect(),
+        order_by: window.order_by.into_iter().map(|item| OrderByItem { expr: transform(item.expr), ..item }).collect(),
+    }
+}
+
+// Returns the value of `left operator right` if both sides are literals of the right kind and the result fits
+fn evaluate_binary(left: &Expression, operator: &BinaryOperator, right: &Expression) -> Option<Expression> {
+    match (left, right) {
+        (Expression::Number(a), Expression::Number(b)) => {
+            let (a, b) = (*a, *b);
+            match operator {
+                BinaryOperator::Plus => a.checked_add(b).map(Expression::Number),
+                BinaryOperator::Minus => a.checked_sub(b).map(Expression::Number),
+                BinaryOperator::Multiply => a.checked_mul(b).map(Expression::Number),
+                BinaryOperator::Divide => a.checked_div(b).map(Expression::Number),
+                BinaryOperator::GreaterThan => Some(Expression::Bool(a > b)),
+                BinaryOperator::GreaterThanOrEqual => Some(Expression::Bool(a >= b)),
+                BinaryOperator::LessThan => Some(Expression::Bool(a < b)),
+                BinaryOperator::LessThanOrEqual => Some(Expression::Bool(a <= b)),
+                BinaryOperator::Equal => Some(Expression::Bool(a == b)),
+                BinaryOperator::NotEqual => Some(Expression::Bool(a != b)),
+                BinaryOperator::And | BinaryOperator::Or => None,
+            }
+        }
+        (Expression::Bool(a), Expression::Bool(b)) => match operator {
+            BinaryOperator::And => Some(Expression::Bool(*a && *b)),
+            BinaryOperator::Or => Some(Expression::Bool(*a || *b)),
+            BinaryOperator::Equal => Some(Expression::Bool(a == b)),
+            BinaryOperator::NotEqual => Some(Expression::Bool(a != b)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flatten_logical, fold_constants};
+    use crate::error::ParseError;
+    use crate::pratt_parsing::parse_expression;
+    use crate::sql_parser::Parser;
+    use crate::statement::{BinaryOperator, Expression};
+    use crate::tokenizer::tokenize;
+
+    fn expression(sql: &str) -> Result<Expression, ParseError> {
+        parse_expression(&mut Parser::new(tokenize(sql)?))
+    }
+
+    fn column(name: &str) -> Expression {
+        Expression::Identifier(name.to_string())
+    }
+
+    fn binary(left: Expression, operator: BinaryOperator, right: Expression) -> Expression {
+        Expression::BinaryOperation { left_operand: Box::new(left), operator, right_operand: Box::new(right) }
+    }
+
+    #[test]
+    fn chains_of_and_become_one_node() {
+        assert_eq!(flatten_logical(expression("a AND b AND c").unwrap()), Expression::And(vec![column("a"), column("b"), column("c")]));
+        assert_eq!(flatten_logical(expression("a OR b OR c").unwrap()), Expression::Or(vec![column("a"), column("b"), column("c")]));
+    }
+
+    #[test]
+    fn different_operators_stay_nested() {
+        // a AND (b OR c) keeps the OR as one operand of the AND
+        assert_eq!(
+            flatten_logical(expression("a AND (b OR c)").unwrap()),
+            Expression::And(vec![column("a"), Expression::Or(vec![column("b"), column("c")])])
+        );
+        // a OR b AND c is a OR (b AND c)
+        assert_eq!(
+            flatten_logical(expression("a OR b AND c AND d").unwrap()),
+            Expression::Or(vec![column("a"), Expression::And(vec![column("b"), column("c"), column("d")])])
+        );
+    }
+
+    #[test]
+    fn flattened_expressions_print_as_sql_that_parses_back() {
+        for sql in ["a AND b AND c", "a AND (b OR c) AND d", "(a OR b) AND (c OR d OR e)", "NOT (a AND b) OR c", "x > 1 AND (y < 2 OR z = 3)"] {
+            let flattened = flatten_logical(expression(sql).unwrap());
+            let printed = flattened.to_string();
+            let reparsed = expression(&printed).unwrap_or_else(|e| panic!("{} printed as {}, which fails with {}", sql, printed, e));
+            assert_eq!(flatten_logical(reparsed), flattened, "{} printed as {}", sql, printed);
+        }
+    }
+
+    #[test]
+    fn literal_arithmetic_is_folded() {
+        assert_eq!(fold_constants(expression("(1 + 2) * 3").unwrap()), Expression::Number(9));
+        assert_eq!(fold_constants(expression("2 + 3 > x").unwrap()), binary(Expression::Number(5), BinaryOperator::GreaterThan, column("x")));
+        assert_eq!(fold_constants(expression("10 / 2 = 5").unwrap()), Expression::Bool(true));
+        assert_eq!(fold_constants(expression("NOT (1 > 2)").unwrap()), Expression::Bool(true));
+        // x + 1 + 2 is (x + 1) + 2, which has a name on the left, so nothing is folded
+        assert_eq!(fold_constants(expression("x + 1 + 2").unwrap()), expression("x + 1 + 2").unwrap());
+    }
+
+    #[test]
+    fn operations_that_cannot_be_computed_are_left_unfolded() {
+        for sql in ["1 / 0", "2 - 3", "18446744073709551615 + 1", "18446744073709551615 * 2"] {
+            assert_eq!(fold_constants(expression(sql).unwrap()), expression(sql).unwrap(), "{}", sql);
+        }
+        // The parts that can be computed are still folded
+        assert_eq!(
+            fold_constants(expression("(1 + 1) / (2 - 2)").unwrap()),
+            binary(Expression::Number(2), BinaryOperator::Divide, Expression::Number(0))
+        );
+    }
+}
@@ -1,18 +1,63 @@
+use std::borrow::Borrow;
+use std::string::String;
 use crate::token::{Token, Token::*, Keyword};
-use crate::statement::{BinaryOperator, Expression,UnaryOperator};
+use crate::statement::{BinaryOperator, DBType, Expression, FloatLiteral, IntervalUnit, Quantifier, UnaryOperator};
 use crate::sql_parser::Parser;
-use std::string::String;
+use crate::error::ParseError;
 //This function is a shortcut that starts parsing an expression
 //In SQL, we can have expressions complex (e.g. with operators, parentheses, etc.).
 // This method kicks off the parsing process by calling the main expression parser with the lowest precedence
-pub fn parse_expression(parser: &mut Parser) -> Result<Expression, std::string::String> {
+pub fn parse_expression(parser: &mut Parser) -> Result<Expression, ParseError> {
     // Start parsing from the lowest priority to handle all operators properly
     parse_binary_expression(parser, 0)
 }
 
 
-pub fn parse_unary_expression(parser: &mut Parser) -> Result<Expression, String> {
+// NOT binds looser than comparisons but tighter than AND, so NOT a = b is NOT (a = b)
+// and NOT a AND b is (NOT a) AND b. Its operand is parsed with this precedence as the minimum, which stops it at AND and OR:
+// a OR NOT b AND c is a OR ((NOT b) AND c). A NOT on the right of a comparison (a = NOT b) still takes only b,
+// and it is written back as a = (NOT b) to make that clear
+pub const NOT_PRECEDENCE: u8 = BinaryOperator::EQUALITY_PRECEDENCE;
+
+// IN binds like the comparisons <, >, ..., so a + 1 IN (2, 3) is (a + 1) IN (2, 3) and a IN (1) AND b is (a IN (1)) AND b
+pub const IN_PRECEDENCE: u8 = BinaryOperator::COMPARISON_PRECEDENCE;
+
+// IS binds like = (and looser than <, >, IN), so a = b IS TRUE is (a = b) IS TRUE, and NOT a IS NULL is NOT (a IS NULL)
+pub const IS_PRECEDENCE: u8 = BinaryOperator::EQUALITY_PRECEDENCE;
+
+// LIKE binds like IN (and the comparisons), so name || x LIKE 'a%' AND b is (... LIKE 'a%') AND b
+pub const LIKE_PRECEDENCE: u8 = BinaryOperator::COMPARISON_PRECEDENCE;
+
+//Every operand goes through here, also the ones in parentheses and after NOT or -, so this is where the nesting depth is counted
+pub fn parse_unary_expression(parser: &mut Parser) -> Result<Expression, ParseError> {
+    parser.enter_nesting()?;
+    let expr = parse_unary_operand(parser);
+    parser.leave_nesting();
+    expr
+}
+
+fn parse_unary_operand(parser: &mut Parser) -> Result<Expression, ParseError> {
     match parser.peek() {
+        //NOT EXISTS (SELECT ...) is one predicate, so it is parsed together instead of as NOT around EXISTS
+        Token::Keyword(Keyword::Not) if matches!(parser.peek_at(1), Token::Keyword(Keyword::Exists)) => {
+            parser.advance();
+            parser.advance();
+            parse_exists(parser, true)
+        }
+
+        //To handle NOT such as NOT active or NOT (a = b)
+        Token::Keyword(Keyword::Not) => {
+            parser.advance();
+
+            // Everything that binds at least as tight as a comparison belongs to the operand
+            let expr = parse_binary_expression(parser, NOT_PRECEDENCE)?;
+
+            Ok(Expression::UnaryOperation {
+                operator: UnaryOperator::Not,
+                operand: Box::new(expr),
+            })
+        }
+
         //To handle unary minus such as -5 or -(-x)
         Token::Minus => {
             parser.advance();
@@ -35,36 +80,231 @@ pub fn parse_unary_expression(parser: &mut Parser) -> Result<Expression, String>
         }
 
         // If it's not a unary operator, delegate to primary expression parser
-        _ => parse_primary_expression(parser),
+        _ => {
+            let expr = parse_primary_expression(parser)?;
+            parse_postfix_cast(parser, expr)
+        }
     }
 }
 
-pub fn parse_primary_expression(parser: &mut Parser) -> Result<Expression, String> {
+pub fn parse_primary_expression(parser: &mut Parser) -> Result<Expression, ParseError> {
+    //DATE '2024-01-01' is a typed literal. DATE, TIME and TIMESTAMP can also be column names,
+    //so only a string right after them makes it a literal (date = '2024-01-01' is still a comparison with the column)
+    let literal_type = match parser.peek() {
+        Keyword(Keyword::Date) => Some(DBType::Date),
+        Keyword(Keyword::Time) => Some(DBType::Time),
+        Keyword(Keyword::Timestamp) => Some(DBType::Timestamp),
+        _ => None,
+    };
+    if let (Some(ty), String(value)) = (literal_type, parser.peek_at(1)) {
+        let value = value.to_string();
+        parser.advance();
+        parser.advance();
+        return Ok(Expression::TypedLiteral { ty, value });
+    }
+    //Some keywords can be used as column names (like `order` or `key`), then they are just identifiers
+    if let Some(name) = parser.keyword_as_identifier(parser.peek()) {
+        parser.advance();
+        return parse_identifier_or_call(parser, name);
+    }
     match parser.advance() {
         //This will allow us grouping like (a+b) and ensures precedence
+        //If there is a comma after the first expression, it isn't a group but a tuple (row value) like (a, b)
         Token::LeftParentheses => {
             let expr = parse_expression(parser)?;
+            if !matches!(parser.peek(), Token::Comma) {
+                return match parser.advance() {
+                    Token::RightParentheses => Ok(expr), // If it found closing paren,it returns the grouped expression
+                    other => Err(ParseError::unexpected("')' after expression", other)), //Error if no closing paren
+                };
+            }
+            let mut elements = vec![expr];
+            while matches!(parser.peek(), Token::Comma) {
+                parser.advance();
+                elements.push(parse_expression(parser)?);
+            }
             match parser.advance() {
-                Token::RightParentheses => Ok(expr), // If it found closing paren,it returns the grouped expression
-                other => Err(format!("Expected ')' after expression, found {:?}", other)), //Error if no closing paren
+                Token::RightParentheses => Ok(Expression::Tuple(elements)),
+                other => Err(ParseError::unexpected("',' or ')' in tuple", other)),
             }
         }
-        Token::Identifier(name) => Ok(Expression::Identifier(name.clone())),
+        Token::Identifier(name) => {
+            let name = name.to_string();
+            parse_identifier_or_call(parser, name)
+        }
         Token::Number(n) => Ok(Expression::Number(*n)),
-        Token::String(s) => Ok(Expression::String(s.clone())),
+        Token::Float(n) => Ok(Expression::Float(FloatLiteral(*n))),
+        Token::Placeholder(placeholder) => Ok(Expression::Placeholder(placeholder.clone())),
+        Token::String(s) => Ok(Expression::String(s.to_string())),
         Token::Keyword(Keyword::True) => Ok(Expression::Bool(true)),
         Token::Keyword(Keyword::False) => Ok(Expression::Bool(false)),
-        other => Err(format!("Unexpected token {:?} - expected primary expression", other)),
+        //UNKNOWN is the third truth value, the result of comparing something with NULL
+        Token::Keyword(Keyword::Unknown) => Ok(Expression::Unknown),
+        // NULL here is just the literal value (like in x = NULL), NOT NULL in constraints is parsed separately
+        Token::Keyword(Keyword::Null) => Ok(Expression::Null),
+        Token::Keyword(Keyword::Exists) => parse_exists(parser, false),
+        Token::Keyword(Keyword::Cast) => parse_cast(parser),
+        Token::Keyword(Keyword::Interval) => parse_interval(parser),
+        other => Err(ParseError::unexpected("primary expression", other)),
+    }
+}
+//EXISTS must be followed by a SELECT in parentheses, the EXISTS keyword itself is already consumed
+fn parse_exists(parser: &mut Parser, negated: bool) -> Result<Expression, ParseError> {
+    let query = parser.parse_subquery("SELECT in EXISTS")?;
+    Ok(Expression::Exists { query: Box::new(query), negated })
+}
+
+//price::INT is the same as CAST(price AS INT). It binds tighter than any operator, also the unary ones,
+//so -a::INT is -(a::INT) and a::INT + b is (a::INT) + b. It can be repeated: a::TEXT::INT
+fn parse_postfix_cast(parser: &mut Parser, mut expr: Expression) -> Result<Expression, ParseError> {
+    while matches!(parser.peek(), DoubleColon) {
+        if !parser.dialect().supports_double_colon_cast() {
+            return Err(ParseError::unexpected("CAST(... AS ...) instead of :: (it is PostgreSQL syntax)", parser.peek()));
+        }
+        parser.advance();
+        let target = parser.parse_column_type()?;
+        expr = Expression::Cast { expr: Box::new(expr), target };
     }
+    Ok(expr)
 }
+
+//INTERVAL '7' DAY is a length of time, the amount is a string or a number and the unit is one keyword
+//The INTERVAL keyword itself is already consumed
+fn parse_interval(parser: &mut Parser) -> Result<Expression, ParseError> {
+    let value = match parser.advance() {
+        String(s) => s.to_string(),
+        Number(n) => n.to_string(),
+        other => return Err(ParseError::unexpected("string or number after INTERVAL", other)),
+    };
+    let unit = match parser.advance() {
+        Keyword(Keyword::Second) => IntervalUnit::Second,
+        Keyword(Keyword::Minute) => IntervalUnit::Minute,
+        Keyword(Keyword::Hour) => IntervalUnit::Hour,
+        Keyword(Keyword::Day) => IntervalUnit::Day,
+        Keyword(Keyword::Month) => IntervalUnit::Month,
+        Keyword(Keyword::Year) => IntervalUnit::Year,
+        other => return Err(ParseError::unexpected("interval unit (SECOND, MINUTE, HOUR, DAY, MONTH, YEAR)", other)),
+    };
+    Ok(Expression::Interval { value, unit })
+}
+
+//CAST(expr AS type) converts a value to another type, the type is written the same way as in CREATE TABLE (like VARCHAR(10))
+//The CAST keyword itself is already consumed
+fn parse_cast(parser: &mut Parser) -> Result<Expression, ParseError> {
+    match parser.advance() {
+        LeftParentheses => {}
+        other => return Err(ParseError::unexpected("'(' after CAST", other)),
+    }
+    let expr = parse_expression(parser)?;
+    match parser.advance() {
+        Keyword(Keyword::As) => {}
+        other => return Err(ParseError::unexpected("AS before the type in CAST", other)),
+    }
+    let target = parser.parse_column_type()?;
+    match parser.advance() {
+        RightParentheses => Ok(Expression::Cast { expr: Box::new(expr), target }),
+        other => Err(ParseError::unexpected("')' after the type in CAST", other)),
+    }
+}
+
+//A name followed by '(' is a function call, like COUNT(id) or UPPER(name), otherwise it is just a column name.
+//A name followed by '.' is a table name, then the column name comes after the dot (users.id)
+fn parse_identifier_or_call(parser: &mut Parser, name: String) -> Result<Expression, ParseError> {
+    if matches!(parser.peek(), Token::Dot) {
+        parser.advance();
+        let column = match parser.keyword_as_identifier(parser.peek()) {
+            Some(column) => column,
+            None => match parser.peek() {
+                Token::Identifier(column) => column.to_string(),
+                other => return Err(ParseError::unexpected("column name after '.'", other)),
+            },
+        };
+        parser.advance();
+        return Ok(Expression::QualifiedIdentifier { table: name, column });
+    }
+    if !matches!(parser.peek(), Token::LeftParentheses) {
+        return Ok(Expression::Identifier(name));
+    }
+    parser.advance();
+
+    //DISTINCT can only be written once, right at the start of the arguments: COUNT(DISTINCT user_id)
+    let distinct = matches!(parser.peek(), Token::Keyword(Keyword::Distinct));
+    if distinct {
+        parser.advance();
+        if matches!(parser.peek(), Token::Keyword(Keyword::Distinct) | Token::Star | Token::RightParentheses) {
+            return Err(ParseError::unexpected("argument after DISTINCT", parser.peek()));
+        }
+    }
+
+    //The arguments are a comma separated list of expressions, it can also be empty like in NOW()
+    //A * is also allowed as a whole argument (COUNT(*)), then it means all columns like in SELECT *.
+    //This is the only place in an expression where * is read as a value: everywhere else a * comes after
+    //an operand, so it is multiplication (COUNT(*) * 2, a * b)
+    let mut args = Vec::new();
+    if !matches!(parser.peek(), Token::RightParentheses) {
+        loop {
+            if matches!(parser.peek(), Token::Star) {
+                parser.advance();
+                args.push(Expression::AllColumns);
+            } else {
+                args.push(parse_expression(parser)?);
+            }
+            if !matches!(parser.peek(), Token::Comma) {
+                break;
+            }
+            parser.advance();
+            if matches!(parser.peek(), Token::RightParentheses) {
+                return Err(ParseError::trailing_comma(parser.peek()));
+            }
+        }
+    }
+    match parser.advance() {
+        Token::RightParentheses => {}
+        other => return Err(ParseError::unexpected("',' or ')' in function arguments", other)),
+    }
+    //OVER (...) after the arguments makes it a window function, like ROW_NUMBER() OVER (ORDER BY salary)
+    let window = if matches!(parser.peek(), Token::Keyword(Keyword::Over)) {
+        parser.advance();
+        Some(parser.parse_window()?)
+    } else {
+        None
+    };
+    Ok(Expression::FunctionCall { name, args, distinct, window })
+}
+
 //This function parses binary expressions using a Pratt parser pattern.
 // It handles operator precedence and associativity (e.g., a + b * c is parsed correctly as a + (b * c))
-pub fn parse_binary_expression(parser: &mut Parser, min_prec: u8) -> Result<Expression, String> {
+pub fn parse_binary_expression(parser: &mut Parser, min_prec: u8) -> Result<Expression, ParseError> {
     //we start by parsing the left-hand side, which could be a number, identifier, or unary expression
     let mut left = parse_unary_expression(parser)?;
 
     // Now we handle binary operators in a loop (like +, -, *, etc.)
-    while let Some(op) = peek_binary_operator(parser) {
+    loop {
+        //IN and NOT IN aren't binary operators, their right side is a list or a subquery, not an expression
+        if is_in_operator(parser) {
+            if IN_PRECEDENCE < min_prec {
+                break;
+            }
+            left = parse_in(parser, left)?;
+            continue;
+        }
+        if is_like_operator(parser) {
+            if LIKE_PRECEDENCE < min_prec {
+                break;
+            }
+            left = parse_like(parser, left)?;
+            continue;
+        }
+        if matches!(parser.peek(), Keyword(Keyword::Is)) {
+            if IS_PRECEDENCE < min_prec {
+                break;
+            }
+            left = parse_is(parser, left)?;
+            continue;
+        }
+        let Some(op) = peek_binary_operator(parser) else {
+            break;
+        };
         let prec = get_precedence(&op);
         if prec < min_prec {
             // If the current operator has lower precedence than what we're expecting, stop here
@@ -73,55 +313,336 @@ pub fn parse_binary_expression(parser: &mut Parser, min_prec: u8) -> Result<Expr
 
         parser.advance();
 
+        // x > ALL (SELECT ...) compares x with every row of the subquery, so the right side is a query, not an expression
+        if let Some(quantifier) = peek_quantifier(parser) {
+            if !op.is_comparison() {
+                return Err(ParseError::unexpected(format!("expression after {} (ALL, ANY and SOME can only follow a comparison)", op), parser.peek()));
+            }
+            parser.advance();
+            let query = parser.parse_subquery(&format!("SELECT after {}", quantifier))?;
+            left = Expression::QuantifiedComparison { expr: Box::new(left), op, quantifier, query: Box::new(query) };
+            continue;
+        }
+
         // Recursively parse the right-hand side with increased precedence
         // This ensures correct grouping like: 1 + 2 * 3 → 1 + (2 * 3)
-        let right = parse_binary_expression(parser, prec + 1)?;
+        // For left-associative operators the right side can't contain the same operator again (a - b - c → (a - b) - c),
+        // while right-associative ones keep the same precedence, so the right side takes the rest (a ^ b ^ c → a ^ (b ^ c))
+        let next_min_prec = match associativity(&op) {
+            Associativity::Left => prec + 1,
+            Associativity::Right => prec,
+        };
+        let right = parse_binary_expression(parser, next_min_prec)?;
 
         //Finally, we combine left and right expressions into a binary operation
+        let is_chained = op.is_comparison() && (is_comparison(&left) || is_comparison(&right));
         left = Expression::BinaryOperation {
             left_operand: Box::new(left),
             operator: op,
             right_operand: Box::new(right),
         };
+        //In strict mode a < b < c is an error instead of (a < b) < c, which compares a boolean with c
+        if is_chained && parser.strict_comparisons() {
+            return Err(ParseError::ChainedComparison { expression: left.to_string() });
+        }
     }
 
     Ok(left)
 }
 
 
+// A comparison with one of the binary operators (=, !=, <, <=, >, >=)
+fn is_comparison(expr: &Expression) -> bool {
+    matches!(expr, Expression::BinaryOperation { operator, .. } if operator.is_comparison())
+}
+
+// LIKE or NOT LIKE
+fn is_like_operator(parser: &Parser) -> bool {
+    match parser.peek() {
+        Keyword(Keyword::Like) => true,
+        Keyword(Keyword::Not) => matches!(parser.peek_at(1), Keyword(Keyword::Like)),
+        _ => false,
+    }
+}
+
+//Parses the rest of `expr [NOT] LIKE pattern [ESCAPE 'c']`. The pattern is an expression like the right side of a comparison,
+//the escape character has to be a string with exactly one character, because it is the one that makes the next % or _ literal
+fn parse_like(parser: &mut Parser, expr: Expression) -> Result<Expression, ParseError> {
+    let negated = matches!(parser.advance(), Keyword(Keyword::Not));
+    if negated {
+        parser.advance();
+    }
+    let pattern = parse_binary_expression(parser, LIKE_PRECEDENCE + 1)?;
+    let escape = if matches!(parser.peek(), Keyword(Keyword::Escape)) {
+        parser.advance();
+        match parser.advance() {
+            String(text) if text.chars().count() == 1 => text.chars().next(),
+            other => return Err(ParseError::unexpected("a string with a single character after ESCAPE", other)),
+        }
+    } else {
+        None
+    };
+    Ok(Expression::Like { expr: Box::new(expr), pattern: Box::new(pattern), negated, escape })
+}
+
+//Parses the rest of `expr IS [NOT] value`, where the value can only be NULL, TRUE, FALSE or UNKNOWN
+fn parse_is(parser: &mut Parser, expr: Expression) -> Result<Expression, ParseError> {
+    parser.advance();
+    let negated = matches!(parser.peek(), Keyword(Keyword::Not));
+    if negated {
+        parser.advance();
+    }
+    let value = match parser.advance() {
+        Keyword(Keyword::Null) => Expression::Null,
+        Keyword(Keyword::True) => Expression::Bool(true),
+        Keyword(Keyword::False) => Expression::Bool(false),
+        Keyword(Keyword::Unknown) => Expression::Unknown,
+        other => return Err(ParseError::unexpected("NULL, TRUE, FALSE or UNKNOWN after IS", other)),
+    };
+    Ok(Expression::Is { expr: Box::new(expr), value: Box::new(value), negated })
+}
+
+// ALL, ANY or SOME after a comparison operator
+fn peek_quantifier(parser: &Parser) -> Option<Quantifier> {
+    match parser.peek() {
+        Keyword(Keyword::All) => Some(Quantifier::All),
+        Keyword(Keyword::Any) => Some(Quantifier::Any),
+        Keyword(Keyword::Some) => Some(Quantifier::Some),
+        _ => None,
+    }
+}
+
+// IN or NOT IN comes next
+fn is_in_operator(parser: &Parser) -> bool {
+    match parser.peek() {
+        Keyword(Keyword::In) => true,
+        Keyword(Keyword::Not) => matches!(parser.peek_at(1), Keyword(Keyword::In)),
+        _ => false,
+    }
+}
+
+//Parses the rest of `expr [NOT] IN (...)`, the parentheses contain either a list of values or one SELECT:
+//x IN (1, 2, 3) or x IN (SELECT id FROM t). If the first thing after '(' is SELECT (or WITH) it is a subquery,
+//so a list can't contain a subquery and a subquery can't be followed by values
+fn parse_in(parser: &mut Parser, expr: Expression) -> Result<Expression, ParseError> {
+    let negated = matches!(parser.advance(), Keyword(Keyword::Not));
+    if negated {
+        parser.advance();
+    }
+    let expr = Box::new(expr);
+    if matches!(parser.peek_at(1), Keyword(Keyword::Select | Keyword::With)) {
+        let query = parser.parse_subquery("SELECT in IN")?;
+        return Ok(Expression::InSubquery { expr, query: Box::new(query), negated });
+    }
+    match parser.advance() {
+        LeftParentheses => {}
+        other => return Err(ParseError::unexpected("'(' after IN", other)),
+    }
+    if matches!(parser.peek(), RightParentheses) {
+        return Err(ParseError::unexpected("value in IN list", parser.peek()));
+    }
+    let mut list = Vec::new();
+    loop {
+        if matches!(parser.peek(), Keyword(Keyword::Select | Keyword::With)) {
+            return Err(ParseError::unexpected("value in IN list (a subquery has to be the only item)", parser.peek()));
+        }
+        list.push(parse_expression(parser)?);
+        if !matches!(parser.peek(), Comma) {
+            break;
+        }
+        parser.advance();
+        if matches!(parser.peek(), RightParentheses) {
+            return Err(ParseError::trailing_comma(parser.peek()));
+        }
+    }
+    match parser.advance() {
+        RightParentheses => Ok(Expression::InList { expr, list, negated }),
+        other => Err(ParseError::unexpected("',' or ')' in IN list", other)),
+    }
+}
+
 //It is used to look ahead at the next token and check if it’s a binary operator
 //If it is, it returns the corresponding BinaryOperator enum variant
 //Some(operator) if the next token is a binary operator (like +, =, AND, etc.)
-//None if it's not
+//None if it's not (which token is which operator is decided by Token::as_binary_operator)
 pub fn peek_binary_operator(parser: &Parser) -> Option<BinaryOperator> {
-    match parser.peek() {
-        Equal => Some(BinaryOperator::Equal),
-        NotEqual => Some(BinaryOperator::NotEqual),
-        GreaterThan => Some(BinaryOperator::GreaterThan),
-        GreaterThanOrEqual => Some(BinaryOperator::GreaterThanOrEqual),
-        LessThan => Some(BinaryOperator::LessThan),
-        LessThanOrEqual => Some(BinaryOperator::LessThanOrEqual),
-        Plus => Some(BinaryOperator::Plus),
-        Minus => Some(BinaryOperator::Minus),
-        Star => Some(BinaryOperator::Multiply),
-        Divide => Some(BinaryOperator::Divide),
-        Keyword(Keyword::And) => Some(BinaryOperator::And),
-        Keyword(Keyword::Or) => Some(BinaryOperator::Or),
-        _ => None,
-    }
+    parser.peek().as_binary_operator()
 }
 
 //In this program, we will use this function to return the precedence(superiority) of different binary operators in SQL expressions
 //In arithmetic and logical operations, precedence determines the order in which operations are applied.
 // This function takes a binary operator and returns a number showing how strong it is when used in an expression (its priority level)
-pub fn get_precedence(op: &BinaryOperator) -> u8 {
-    match op {
-        BinaryOperator::Or => 1,
-        BinaryOperator::And => 2,
-        BinaryOperator::Equal | BinaryOperator::NotEqual => 3,
+// It takes the operator by value or by reference, whichever the caller has
+pub fn get_precedence(op: impl Borrow<BinaryOperator>) -> u8 {
+    match op.borrow() {
+        BinaryOperator::Or => BinaryOperator::OR_PRECEDENCE,
+        BinaryOperator::And => BinaryOperator::AND_PRECEDENCE,
+        BinaryOperator::Equal | BinaryOperator::NotEqual => BinaryOperator::EQUALITY_PRECEDENCE,
         BinaryOperator::GreaterThan | BinaryOperator::GreaterThanOrEqual
-        | BinaryOperator::LessThan | BinaryOperator::LessThanOrEqual => 4,
-        BinaryOperator::Plus | BinaryOperator::Minus => 5,
-        BinaryOperator::Multiply | BinaryOperator::Divide => 6,
+        | BinaryOperator::LessThan | BinaryOperator::LessThanOrEqual => BinaryOperator::COMPARISON_PRECEDENCE,
+        BinaryOperator::Plus | BinaryOperator::Minus => BinaryOperator::ADDITIVE_PRECEDENCE,
+        BinaryOperator::Multiply | BinaryOperator::Divide => BinaryOperator::MULTIPLICATIVE_PRECEDENCE,
+    }
+}
+
+// The precedence of an operator, for tools outside of the parser that need to put operators in the same order
+// (a higher number binds tighter, the levels are the *_PRECEDENCE constants on BinaryOperator)
+pub fn precedence_of(op: &BinaryOperator) -> u8 {
+    get_precedence(op)
+}
+
+// Tells in which direction a chain of operators with the same precedence is grouped
+// Left: a - b - c is (a - b) - c, Right: a ^ b ^ c would be a ^ (b ^ c)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+// All operators we have right now are left-associative, a right-associative one (like an exponent ^)
+// only needs to be listed here to be parsed the other way
+pub fn associativity(op: &BinaryOperator) -> Associativity {
+    #[cfg(test)]
+    if tests::is_right_associative(op) {
+        return Associativity::Right;
+    }
+    match op {
+        BinaryOperator::Or | BinaryOperator::And
+        | BinaryOperator::Equal | BinaryOperator::NotEqual
+        | BinaryOperator::GreaterThan | BinaryOperator::GreaterThanOrEqual
+        | BinaryOperator::LessThan | BinaryOperator::LessThanOrEqual
+        | BinaryOperator::Plus | BinaryOperator::Minus
+        | BinaryOperator::Multiply | BinaryOperator::Divide => Associativity::Left,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use super::{associativity, parse_expression, Associativity};
+    use crate::sql_parser::{Parser, DEFAULT_MAX_DEPTH};
+    use crate::statement::{BinaryOperator, DBType, Expression, UnaryOperator};
+    use crate::error::ParseError;
+    use crate::tokenizer::tokenize;
+
+    fn expression(sql: &str) -> Result<Expression, ParseError> {
+        parse_expression(&mut Parser::new(tokenize(sql)?))
+    }
+
+    thread_local! {
+        // None of the real operators is right-associative, so a test can make one of them right-associative
+        // (only on its own thread) to check how the parser and Display handle such an operator
+        static RIGHT_ASSOCIATIVE: RefCell<Option<BinaryOperator>> = const { RefCell::new(None) };
+    }
+
+    pub(super) fn is_right_associative(op: &BinaryOperator) -> bool {
+        RIGHT_ASSOCIATIVE.with(|right| right.borrow().as_ref() == Some(op))
+    }
+
+    fn column(name: &str) -> Expression {
+        Expression::Identifier(name.to_string())
+    }
+
+    fn binary(left: Expression, operator: BinaryOperator, right: Expression) -> Expression {
+        Expression::BinaryOperation { left_operand: Box::new(left), operator, right_operand: Box::new(right) }
+    }
+
+    fn not(operand: Expression) -> Expression {
+        Expression::UnaryOperation { operator: UnaryOperator::Not, operand: Box::new(operand) }
+    }
+
+    fn too_deep() -> Result<Expression, ParseError> {
+        Err(ParseError::NestingTooDeep { max_depth: DEFAULT_MAX_DEPTH })
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn thousands_of_parentheses_are_too_deep() {
+        assert_eq!(expression(&"(".repeat(100_000)), too_deep());
+        assert_eq!(expression(&format!("{}a{}", "(".repeat(100_000), ")".repeat(100_000))), too_deep());
+    }
+
+    #[test]
+    fn long_not_and_minus_chains_are_too_deep() {
+        assert_eq!(expression(&format!("{}a", "NOT ".repeat(100_000))), too_deep());
+        // With a space between them, because -- would start a comment
+        assert_eq!(expression(&format!("{}a", "- ".repeat(100_000))), too_deep());
+    }
+
+    #[test]
+    fn nesting_limit_is_exact() {
+        let wrapped = |n: usize| format!("{}a{}", "(".repeat(n), ")".repeat(n));
+        assert_eq!(expression(&wrapped(DEFAULT_MAX_DEPTH)), Ok(Expression::Identifier("a".to_string())));
+        assert_eq!(expression(&wrapped(DEFAULT_MAX_DEPTH + 1)), too_deep());
+        assert!(expression(&format!("{}a", "NOT ".repeat(DEFAULT_MAX_DEPTH))).is_ok());
+        assert_eq!(expression(&format!("{}a", "NOT ".repeat(DEFAULT_MAX_DEPTH + 1))), too_deep());
+
+        let with_max_depth_3 = |sql: &str| parse_expression(&mut Parser::new(tokenize(sql).unwrap()).with_max_depth(3));
+        assert!(with_max_depth_3(&wrapped(3)).is_ok());
+        assert_eq!(with_max_depth_3(&wrapped(4)), Err(ParseError::NestingTooDeep { max_depth: 3 }));
+    }
+
+    #[test]
+    fn operators_of_the_same_precedence_group_from_the_left() {
+        use BinaryOperator::*;
+        // a - b - c is (a - b) - c, not a - (b - c)
+        assert_eq!(expression("a - b - c"), Ok(binary(binary(column("a"), Minus, column("b")), Minus, column("c"))));
+        assert_eq!(expression("a / b / c"), Ok(binary(binary(column("a"), Divide, column("b")), Divide, column("c"))));
+        assert_eq!(expression("a - b + c"), Ok(binary(binary(column("a"), Minus, column("b")), Plus, column("c"))));
+        assert_eq!(expression("a * b / c"), Ok(binary(binary(column("a"), Multiply, column("b")), Divide, column("c"))));
+        assert_eq!(expression("a - (b - c)"), Ok(binary(column("a"), Minus, binary(column("b"), Minus, column("c")))));
+
+        for op in [Plus, Minus, Multiply, Divide, GreaterThan, GreaterThanOrEqual, LessThan, LessThanOrEqual, Equal, NotEqual, And, Or] {
+            assert!(matches!(associativity(&op), Associativity::Left), "{:?} should be left-associative", op);
+        }
+    }
+
+    #[test]
+    fn not_takes_the_whole_comparison_but_stops_at_and_or() {
+        use BinaryOperator::*;
+        // NOT a = b is NOT (a = b)
+        assert_eq!(expression("NOT a = b"), Ok(not(binary(column("a"), Equal, column("b")))));
+        // NOT a AND b is (NOT a) AND b
+        assert_eq!(expression("NOT a AND b"), Ok(binary(not(column("a")), And, column("b"))));
+        // NOT a IN (1, 2) is NOT (a IN (1, 2))
+        assert_eq!(
+            expression("NOT a IN (1, 2)"),
+            Ok(not(Expression::InList { expr: Box::new(column("a")), list: vec![Expression::Number(1), Expression::Number(2)], negated: false }))
+        );
+        // a OR NOT b is a OR (NOT b), and a OR NOT b AND c is a OR ((NOT b) AND c)
+        assert_eq!(expression("a OR NOT b"), Ok(binary(column("a"), Or, not(column("b")))));
+        assert_eq!(expression("a OR NOT b AND c"), Ok(binary(column("a"), Or, binary(not(column("b")), And, column("c")))));
+    }
+
+    #[test]
+    fn right_associative_operator_groups_from_the_right() {
+        use BinaryOperator::*;
+        RIGHT_ASSOCIATIVE.with(|right| *right.borrow_mut() = Some(Minus));
+        assert!(matches!(associativity(&Minus), Associativity::Right));
+
+        // a - b - c is now a - (b - c), and it is written back without parentheses
+        let nested_right = binary(column("a"), Minus, binary(column("b"), Minus, column("c")));
+        assert_eq!(expression("a - b - c"), Ok(nested_right));
+        assert_eq!(expression("a - b - c").unwrap().to_string(), "a - b - c");
+
+        // The other grouping needs parentheses on the left to survive a round trip
+        let nested_left = binary(binary(column("a"), Minus, column("b")), Minus, column("c"));
+        assert_eq!(nested_left.to_string(), "(a - b) - c");
+        assert_eq!(expression(&nested_left.to_string()), Ok(nested_left));
+
+        // Operators with a different precedence are not affected
+        assert_eq!(expression("a - b * c - d"), Ok(binary(column("a"), Minus, binary(binary(column("b"), Multiply, column("c")), Minus, column("d")))));
+        RIGHT_ASSOCIATIVE.with(|right| *right.borrow_mut() = None);
+    }
+
+    #[test]
+    fn cast_binds_tighter_than_other_operators() {
+        let cast = |operand: Expression| Expression::Cast { expr: Box::new(operand), target: DBType::Int };
+        // a::INT + b is CAST(a AS INT) + b
+        assert_eq!(expression("a::INT + b"), Ok(binary(cast(column("a")), BinaryOperator::Plus, column("b"))));
+        // -a::INT is -(CAST(a AS INT))
+        assert_eq!(
+            expression("-a::INT"),
+            Ok(Expression::UnaryOperation { operator: UnaryOperator::Minus, operand: Box::new(cast(column("a"))) })
+        );
+    }
+}
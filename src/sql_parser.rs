@@ -1,52 +1,426 @@
 use std::string::String;
-use crate::token::{Token, Token::*, Keyword};
-use crate::statement::{UnaryOperator, *};
+use crate::token::{Token, Token::*, Keyword, Span};
+use crate::statement::*;
 use crate::pratt_parsing::parse_expression;
+use crate::error::ParseError;
+use crate::dialect::Dialect;
+
+// Keywords that can still be used as names (of columns, tables, ...) when a name is expected,
+// because people often have columns called `key` or `order`. Keywords that can start an expression
+// or a clause (NOT, NULL, SELECT, FROM, ...) can't be here, they would make the query ambiguous
+pub const DEFAULT_NON_RESERVED_KEYWORDS: &[Keyword] = &[
+    Keyword::Key,
+    Keyword::Check,
+    Keyword::Order,
+    Keyword::Group,
+    Keyword::Rollup,
+    Keyword::Partition,
+    Keyword::Column,
+    Keyword::Add,
+    Keyword::Asc,
+    Keyword::Desc,
+    Keyword::Text,
+    Keyword::Date,
+    Keyword::Time,
+    Keyword::Timestamp,
+    Keyword::First,
+    Keyword::Last,
+    Keyword::Second,
+    Keyword::Minute,
+    Keyword::Hour,
+    Keyword::Day,
+    Keyword::Month,
+    Keyword::Year,
+    Keyword::Delete,
+    Keyword::Update,
+    Keyword::Cascade,
+    Keyword::Restrict,
+    Keyword::Set,
+    Keyword::No,
+    Keyword::Action,
+    Keyword::Minus,
+    Keyword::Index,
+    Keyword::Generated,
+    Keyword::Always,
+    Keyword::Stored,
+    Keyword::Virtual,
+    Keyword::Analyze,
+];
 
 // This struct holds the list of tokens and keeps track of the current position
-pub struct Parser {
-    tokens: Vec<Token>,
+// It can be cloned to try parsing something and throw the copy away if it doesn't work out. A clone copies the whole
+// token list (the text of most tokens is borrowed from the input, so only the references are copied), so it costs
+// as much as the input is long. To go back inside one parser, position() and reset_to() are enough and cost nothing
+#[derive(Clone)]
+pub struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    // Where each token is in the input (the same index as in tokens), empty when the parser was given only tokens
+    spans: Vec<Span>,
     current: usize,
+    non_reserved: Vec<Keyword>,
+    // How deep we are in nested expressions and subqueries right now, and how deep we allow to go
+    depth: usize,
+    max_depth: usize,
+    options: ParserOptions,
+}
+
+/// Settings that change what the parser accepts. `ParserOptions::default()` is the strict behaviour that `Parser::new` uses.
+/// 1. `require_semicolon` – Every statement has to end with `;`. When it is `false`, the end of the input also ends the last statement, which is handy for passing single statements from code (`parse` works like this).
+/// 2. `dialect` – Which database's extensions are accepted, like `LIMIT offset, count` in `Dialect::MySql` (see `Dialect`). The default, `Dialect::Generic`, accepts all of them.
+/// 3. `max_char_length` – The largest length allowed in `VARCHAR(n)` and `CHAR(n)`, `DEFAULT_MAX_CHAR_LENGTH` by default. `None` allows any length (a length of `0` is always rejected).
+/// 4. `strict_comparisons` – Reject a comparison of a comparison, like `a < b < c` (which would be read as `(a < b) < c`, almost never what was meant). Off by default.
+/// 5. `allow_trailing_comma` – Accept a comma right before the `)` that closes the column list of a `CREATE TABLE` or a row of `INSERT ... VALUES`, like `CREATE TABLE t (a INT, b INT,);` or `VALUES (1, 2,)`, which some generators write. The comma doesn't add a value to the row. Off by default, then it is a `ParseError::TrailingComma`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParserOptions {
+    pub require_semicolon: bool,
+    pub dialect: Dialect,
+    pub max_char_length: Option<usize>,
+    pub strict_comparisons: bool,
+    pub allow_trailing_comma: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            require_semicolon: true,
+            dialect: Dialect::default(),
+            max_char_length: Some(DEFAULT_MAX_CHAR_LENGTH),
+            strict_comparisons: false,
+            allow_trailing_comma: false,
+        }
+    }
 }
 
+// The longest VARCHAR(n) or CHAR(n) that is accepted by default, the same as the largest row MySQL allows
+pub const DEFAULT_MAX_CHAR_LENGTH: usize = 65535;
+
+// Every nested expression (parentheses, NOT, unary minus, subqueries) is parsed with recursion,
+// so without a limit an input like ((((...)))) with thousands of parentheses would overflow the stack.
+// Each level takes a few kilobytes of stack in a debug build (a subquery counts at least twice, itself and the EXISTS or IN around it),
+// so the default is low enough to stay inside the 2 MiB that a spawned thread gets
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
 // In this block, we will create a new parser from a list of tokens
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+impl<'a> Parser<'a> {
+    // The tokens can come from anything that yields them (a Vec, an iterator over the Tokenizer, ...).
+    // We keep them in a vector because the parser sometimes needs to look a few tokens ahead
+    pub fn new(tokens: impl IntoIterator<Item = Token<'a>>) -> Self {
+        Self::with_options(tokens, ParserOptions::default())
+    }
+
+    // The same as new(), but with options that change what is accepted (like statements without a semicolon)
+    pub fn with_options(tokens: impl IntoIterator<Item = Token<'a>>, options: ParserOptions) -> Self {
+        Parser {
+            // Comments (from tokenize_with_comments) don't mean anything to the grammar, so they are dropped here
+            tokens: tokens.into_iter().filter(|token| !matches!(token, Token::Comment(_))).collect(),
+            spans: Vec::new(),
+            current: 0,
+            non_reserved: DEFAULT_NON_RESERVED_KEYWORDS.to_vec(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            options,
+        }
+    }
+
+    // The same as with_options(), but the tokens come with their spans (from tokenize_with_spans),
+    // so the errors returned by parse() can tell where in the input they are (see ParseError::span)
+    pub fn with_spans(tokens: impl IntoIterator<Item = (Token<'a>, Span)>, options: ParserOptions) -> Self {
+        let (tokens, spans): (Vec<Token<'a>>, Vec<Span>) =
+            tokens.into_iter().filter(|(token, _)| !matches!(token, Token::Comment(_))).unzip();
+        let mut parser = Self::with_options(tokens, options);
+        parser.spans = spans;
+        parser
+    }
+
+    // Changes how deeply expressions may be nested before parsing fails (see DEFAULT_MAX_DEPTH)
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
     }
 
-    // The parse() method looks at the first token (peek()) and decides which kind of SQL statement to parse (SELECT or CREATE)
+    // Replaces the set of keywords that may be used as names (see DEFAULT_NON_RESERVED_KEYWORDS).
+    // Passing an empty list makes every keyword reserved
+    pub fn with_non_reserved_keywords(mut self, keywords: impl IntoIterator<Item = Keyword>) -> Self {
+        self.non_reserved = keywords.into_iter().collect();
+        self
+    }
+
+    // The dialect the parser was created with, some expressions are only allowed in one of them
+    pub(crate) fn dialect(&self) -> Dialect {
+        self.options.dialect
+    }
+
+    // Whether a comparison of a comparison (a < b < c) is an error, see ParserOptions::strict_comparisons
+    pub(crate) fn strict_comparisons(&self) -> bool {
+        self.options.strict_comparisons
+    }
+
+    // If the token is a keyword that may be used as a name, this returns that name.
+    // The tokenizer doesn't keep the original spelling of keywords, so the name is always lowercase (ORDER -> order)
+    pub fn keyword_as_identifier(&self, token: &Token<'_>) -> Option<String> {
+        match token {
+            Keyword(keyword) if self.non_reserved.contains(keyword) => Some(keyword.to_string().to_lowercase()),
+            _ => None,
+        }
+    }
+
+    // The parse() method looks at the first token (peek()) and decides which kind of SQL statement to parse (SELECT, CREATE, ALTER, TRUNCATE, EXPLAIN, INSERT, UPDATE or DELETE)
     // If it is Select keyword, then we will parse_select() method
-    // In other case, we will call parse_create() method
-    pub fn parse(&mut self) -> Result<Statement, String> {
+    // For Create keyword, we will call parse_create() method, for Alter, parse_alter() and for Truncate, parse_truncate()
+    pub fn parse(&mut self) -> Result<Statement, ParseError> {
+        self.parse_statement().map_err(|mut error| {
+            if let Some(span) = self.error_span(&error) {
+                error.set_span(span);
+            }
+            error
+        })
+    }
+
+    fn parse_statement(&mut self) -> Result<Statement, ParseError> {
         match self.peek() {
-            Token::Keyword(Keyword::Select) => self.parse_select(),
+            Token::Keyword(Keyword::Select | Keyword::With) => self.parse_select(),
             Token::Keyword(Keyword::Create) => self.parse_create(),
-            // If it's neither, it returns an error
-            _ => Err("Expected SELECT or CREATE statement".to_string()),
+            Token::Keyword(Keyword::Alter) => self.parse_alter(),
+            Token::Keyword(Keyword::Truncate) => self.parse_truncate(),
+            Token::Keyword(Keyword::Explain) => self.parse_explain(),
+            Token::Keyword(Keyword::Insert) => self.parse_insert(),
+            Token::Keyword(Keyword::Update) => self.parse_update(),
+            Token::Keyword(Keyword::Delete) => self.parse_delete(),
+            // Nothing at all, or a semicolon with nothing before it, gets its own error instead of "Expected SELECT ..."
+            // The semicolon isn't consumed, so parse_and_recover skips exactly that one
+            Token::Eof if self.current == 0 => Err(ParseError::EmptyInput),
+            Token::Semicolon => Err(ParseError::EmptyStatement { span: None }),
+            // If it's none of them, it returns an error
+            other => Err(ParseError::unexpected("SELECT, CREATE, ALTER, TRUNCATE, EXPLAIN, INSERT, UPDATE or DELETE statement", other)),
+        }
+    }
+
+    // EXPLAIN [ANALYZE] statement; asks the database how it would run the statement (with ANALYZE it also runs it)
+    // The inner statement is parsed as usual, with its own semicolon. Explaining an EXPLAIN (or nothing) makes no sense, so it is an error
+    fn parse_explain(&mut self) -> Result<Statement, ParseError> {
+        self.expect_keyword_any_line(Keyword::Explain)?;
+        let analyze = self.match_keyword(Keyword::Analyze);
+        if analyze {
+            self.advance();
+        }
+        if matches!(self.peek(), Keyword(Keyword::Explain) | Semicolon | Eof) {
+            return Err(ParseError::unexpected("statement after EXPLAIN", self.peek()));
+        }
+        let inner = self.parse_statement()?;
+        Ok(Statement::Explain { analyze, inner: Box::new(inner) })
+    }
+
+    // The same as parse(), but if the statement is broken, the rest of it is skipped (up to and including its semicolon),
+    // so the next call starts at the next statement instead of in the middle of the broken one
+    pub fn parse_and_recover(&mut self) -> Result<Statement, ParseError> {
+        let start = self.current;
+        let result = self.parse();
+        if result.is_err() {
+            // The token that caused the error may already be consumed, if it was the semicolon we are already past the statement
+            let consumed_semicolon = self.current > start && matches!(self.tokens.get(self.current - 1), Some(Semicolon));
+            if !consumed_semicolon {
+                while !matches!(self.peek(), Semicolon | Eof) {
+                    self.advance();
+                }
+                if self.match_token(Semicolon) {
+                    self.advance();
+                }
+            }
+        }
+        result
+    }
+
+    // A whole SELECT statement is the query itself followed by a semicolon
+    fn parse_select(&mut self) -> Result<Statement, ParseError> {
+        let select = self.parse_select_body()?;
+        // This line is calling the expect_semicolon() method, which checks if the next token is a semicolon (;)
+        // If we miss the semicolon at the end, it will return error in return
+        self.expect_semicolon()?;
+        Ok(select)
+    }
+
+    // A subquery is a SELECT in parentheses (like in EXISTS (SELECT ...)), it doesn't have its own semicolon
+    // `context` says where the subquery is, like "SELECT in EXISTS", so the error tells what was missing
+    // Subqueries can contain subqueries, so they count as a level of nesting too
+    pub(crate) fn parse_subquery(&mut self, context: &str) -> Result<Statement, ParseError> {
+        self.enter_nesting()?;
+        let query = self.parse_subquery_inner(context);
+        self.leave_nesting();
+        query
+    }
+
+    fn parse_subquery_inner(&mut self, context: &str) -> Result<Statement, ParseError> {
+        self.expect_token_any_line(Token::LeftParentheses)?;
+        if !matches!(self.peek(), Keyword(Keyword::Select | Keyword::With)) {
+            return Err(ParseError::unexpected(context, self.peek()));
+        }
+        let query = self.parse_select_body()?;
+        self.expect_token_any_line(Token::RightParentheses)?;
+        Ok(query)
+    }
+
+    // A WITH clause in front of the SELECT defines named queries that the SELECT can use like tables:
+    // WITH recent AS (SELECT ...), totals (id, sum) AS (SELECT ...) SELECT * FROM recent;
+    fn parse_with(&mut self) -> Result<Vec<CommonTableExpression>, ParseError> {
+        let mut ctes = Vec::new();
+        if !self.match_keyword(Keyword::With) {
+            return Ok(ctes);
+        }
+        self.advance();
+        loop {
+            let name = self.expect_identifier("name of the query after WITH")?;
+            // The column names are optional: name (a, b) AS (...)
+            let columns = if self.match_token(Token::LeftParentheses) {
+                self.parse_column_name_list()?
+            } else {
+                Vec::new()
+            };
+            self.expect_keyword_any_line(Keyword::As)?;
+            let query = self.parse_subquery("SELECT in WITH")?;
+            ctes.push(CommonTableExpression { name, columns, query: Box::new(query) });
+
+            if !self.match_token(Comma) {
+                break;
+            }
+            self.advance();
         }
+        Ok(ctes)
     }
 
-    // It expects the keyword SELECT to appear first. If it's not there, it will return an error and stop
-    fn parse_select(&mut self) -> Result<Statement, String> {
+    // A query is one SELECT or several of them combined with UNION, EXCEPT (MINUS) and INTERSECT
+    // This reads the query up to (but without) the semicolon, so it can also be used for subqueries
+    fn parse_select_body(&mut self) -> Result<Statement, ParseError> {
+        self.parse_set_operation(0)
+    }
+
+    // The set operators are parsed like binary operators in parse_binary_expression: INTERSECT binds tighter than
+    // UNION and EXCEPT, so a UNION b INTERSECT c is a UNION (b INTERSECT c), and operators of the same level go from left to right
+    // A WITH clause can only be at the very beginning, it belongs to the first SELECT
+    fn parse_set_operation(&mut self, min_precedence: u8) -> Result<Statement, ParseError> {
+        let mut left = self.parse_simple_select()?;
+        while let Some(op) = self.peek_set_operator()? {
+            let precedence = op.precedence();
+            if precedence < min_precedence {
+                break;
+            }
+            self.advance();
+            // UNION ALL keeps duplicate rows, UNION DISTINCT is the same as UNION without anything
+            let all = self.match_keyword(Keyword::All);
+            if all || self.match_keyword(Keyword::Distinct) {
+                self.advance();
+            }
+            if !self.match_keyword(Keyword::Select) {
+                return Err(ParseError::unexpected(format!("SELECT after {}", op), self.peek()));
+            }
+            let right = self.parse_set_operation(precedence + 1)?;
+            left = Statement::SetOperation { op, all, left: Box::new(left), right: Box::new(right) };
+        }
+        Ok(left)
+    }
+
+    // The set operator at the current token, if there is one
+    fn peek_set_operator(&self) -> Result<Option<SetOp>, ParseError> {
+        Ok(match self.peek() {
+            Keyword(Keyword::Union) => Some(SetOp::Union),
+            Keyword(Keyword::Except) => Some(SetOp::Except),
+            Keyword(Keyword::Intersect) => Some(SetOp::Intersect),
+            Keyword(Keyword::Minus) if self.options.dialect.supports_minus() => Some(SetOp::Except),
+            Keyword(Keyword::Minus) => return Err(ParseError::unexpected("UNION, EXCEPT or INTERSECT (MINUS is Oracle syntax)", self.peek())),
+            _ => None,
+        })
+    }
+
+    // It expects the keyword SELECT to appear first (after an optional WITH clause). If it's not there, it will return an error and stop
+    // This reads one SELECT, without the set operators after it
+    fn parse_simple_select(&mut self) -> Result<Statement, ParseError> {
+        let ctes = self.parse_with()?;
         self.expect_keyword_any_line(Keyword::Select)?;
-        // This creates an empty list called columns to store the columns selected in the query (like SELECT name, age)
+        // The columns selected in the query (like SELECT name, age), up to FROM
+        let columns = self.parse_select_list(|token| matches!(token, Keyword(Keyword::From)), "FROM")?;
+
+        // Parsing FROM clause (table name)
+        self.expect_keyword_any_line(Keyword::From)?;
+        let from = self.parse_table_name("table name after FROM")?;
+
+        // We can have WHERE keyword also:
+        let r#where = self.parse_where()?;
+
+        // GROUP BY, its list can be followed by WITH ROLLUP (the MySQL way of adding subtotal rows)
+        let mut group_by = Vec::new();
+        let mut with_rollup = false;
+        if self.match_keyword(Keyword::Group) {
+            self.advance();
+            self.expect_keyword_any_line(Keyword::By)?;
+            loop {
+                group_by.push(parse_expression(self)?);
+                if !self.match_token(Comma) { break; }
+                self.advance();
+            }
+            if self.match_keyword(Keyword::With) {
+                if !self.options.dialect.supports_with_rollup() {
+                    return Err(ParseError::unexpected("ORDER BY, LIMIT or the end of the query (WITH ROLLUP is MySQL syntax)", self.peek()));
+                }
+                self.advance();
+                self.expect_keyword_any_line(Keyword::Rollup)?;
+                with_rollup = true;
+            }
+        }
+
+        // ORDER BY:
+        let mut orderby = Vec::new();
+        if self.match_keyword(Keyword::Order) {
+            self.advance();
+            // After ORDER keyword,we expect the next keyword to be "BY", return an error
+            self.expect_keyword_any_line(Keyword::By)?;
+            orderby = self.parse_order_by_list()?;
+            check_ordinals(&orderby, &columns)?;
+        }
+        let limit = self.parse_limit()?;
+
+        // Every clause that was written in the right place is consumed by now, so if the next token starts one of them,
+        // it came too late (WHERE after ORDER BY) or a second time. The last clause that was read is the one it should have been before
+        let last = if limit.is_some() {
+            "LIMIT"
+        } else if !orderby.is_empty() {
+            "ORDER BY"
+        } else if !group_by.is_empty() {
+            "GROUP BY"
+        } else if r#where.is_some() {
+            "WHERE"
+        } else {
+            "FROM"
+        };
+        self.check_clause_order(last)?;
+
+        // Return the parsed SELECT statement, including the columns, FROM clause, optional WHERE clause,
+        // the ORDER BY expressions collected above, the LIMIT and the GROUP BY list
+        Ok(Statement::Select { columns, from, r#where, orderby, ctes, limit, group_by, with_rollup })
+    }
+
+    // The columns of a SELECT (up to FROM) or of RETURNING (up to the end of the statement): expressions, * and table.*,
+    // separated by commas. `is_end` tells which token ends the list, and `end` is how the errors describe that token
+    fn parse_select_list(&mut self, is_end: fn(&Token<'_>) -> bool, end: &str) -> Result<Vec<Expression>, ParseError> {
         let mut columns = Vec::new();
 
-        // Keep collecting column expressions until we see FROM
+        // Keep collecting column expressions until we see the end of the list
         loop {
             match self.peek() {
                 Token::Star => {
                     // Here, we are handling with SELECT *
                     self.advance();
-                    columns.push(Expression::AllColumns); // Make sure you add this variant in your Expression enum
+                    columns.push(Expression::AllColumns);
                 }
-                Token::Keyword(Keyword::From) => {
-                    // If FROM appears and no columns collected, it's an error
-                    if columns.is_empty() {
-                        return Err("Expected at least one column before FROM".to_string());
-                    }
-                    break; // End of column list
+                // The end right at the start means there are no columns at all (after a comma it is a trailing comma, see below)
+                token if is_end(token) => {
+                    return Err(ParseError::unexpected(format!("at least one column before {}", end), token));
+                }
+                // table.* selects all columns of one table. It is only allowed as a whole column,
+                // so something like users.* + 1 fails at the check for ',' below
+                _ if matches!(self.peek_at(1), Token::Dot) && matches!(self.peek_at(2), Token::Star) => {
+                    let table = self.expect_identifier("table name before .*")?;
+                    self.advance();
+                    self.advance();
+                    columns.push(Expression::QualifiedAllColumns(table));
                 }
                 _ => {
                     // if we don't encounter with * star, then we will return as a normal expression
@@ -55,169 +429,226 @@ impl Parser {
                 }
             }
 
-            // We are expecting comma or FROM after each column
+            // We are expecting comma or the end of the list after each column
             match self.peek() {
                 Comma => {
                     self.advance();
-                    // After a comma, ensure the next token is not FROM (no trailing comma allowed)
-                    if self.match_keyword(Keyword::From) {
-                        return Err("Trailing comma before FROM is not allowed".to_string());
+                    // After a comma, ensure the list doesn't end (no trailing comma allowed)
+                    if is_end(self.peek()) {
+                        return Err(ParseError::trailing_comma(self.peek()));
                     }
                 }
-                Token::Keyword(Keyword::From) => break, // if it is From, we will think it as end of column
-                _ => return Err(format!("Expected ',' or FROM, found {:?}", self.peek())),
+                token if is_end(token) => break,
+                _ => return Err(ParseError::unexpected(format!("',' or {}", end), self.peek())),
             }
         }
+        Ok(columns)
+    }
 
-        // Parsing FROM clause (table name)
-        self.expect_keyword_any_line(Keyword::From)?;
-        let from = match self.advance() {
-            Identifier(name) => name.clone(),
-            _ => return Err("Expected table name after FROM".to_string()),
-        };
+    // The optional WHERE filter of SELECT, UPDATE and DELETE
+    fn parse_where(&mut self) -> Result<Option<Expression>, ParseError> {
+        if !self.match_keyword(Keyword::Where) {
+            return Ok(None);
+        }
+        self.advance();
+        Ok(Some(parse_expression(self)?))
+    }
 
-        // We can have WHERE keyword also:
-        let r#where = if self.match_keyword(Keyword::Where) {
-            self.advance();
-            Some(parse_expression(self)?)
-        } else {
-            None
-        };
+    // RETURNING at the end of INSERT, UPDATE and DELETE, its columns are read like the columns of a SELECT.
+    // Nothing after them belongs to the statement, so the list goes up to the semicolon (or the end of the input)
+    fn parse_returning(&mut self) -> Result<Vec<Expression>, ParseError> {
+        if !self.match_keyword(Keyword::Returning) {
+            return Ok(Vec::new());
+        }
+        if !self.options.dialect.supports_returning() {
+            return Err(ParseError::unexpected("the end of the statement (RETURNING is PostgreSQL syntax)", self.peek()));
+        }
+        self.advance();
+        self.parse_select_list(|token| matches!(token, Semicolon | Eof), "the end of the statement")
+    }
 
-        // ORDER BY:
+    // The items of an ORDER BY, after the BY. It is used for the ORDER BY of a SELECT and inside OVER (...)
+    pub(crate) fn parse_order_by_list(&mut self) -> Result<Vec<OrderByItem>, ParseError> {
         let mut orderby = Vec::new();
-        if self.match_keyword(Keyword::Order) {
-            self.advance();
-            // After ORDER keyword,we expect the next keyword to be "BY", return an error
-            self.expect_keyword_any_line(Keyword::By)?;
-            loop {
-                let mut expr = parse_expression(self)?;
+        loop {
+            let expr = parse_expression(self)?;
 
-                // Check if the next token is a sorting direction keyword: ASC or DESC
-                match self.peek() {
-                    // If "ASC", advance the token stream and wrap the expression with a unary operation for ascending order
-                    Keyword(Keyword::Asc) => {
-                        self.advance();
-                        expr = Expression::UnaryOperation {
-                            operand: Box::new(expr),
-                            operator: UnaryOperator::Asc,
-                        };
-                    }
-                    // If "DESC", advance the token stream and wrap the expression similarly for descending order
-                    Keyword(Keyword::Desc) => {
-                        self.advance();
-                        expr = Expression::UnaryOperation {
-                            operand: Box::new(expr),
-                            operator: UnaryOperator::Desc,
-                        };
-                    }
-                    // Otherwise, no explicit order direction is specified (default order assumed)
-                    _ => {}
+            // Check if the next token is a sorting direction keyword: ASC or DESC
+            // If there is none, no explicit order direction is specified (default order assumed)
+            let direction = match self.peek() {
+                Keyword(Keyword::Asc) => {
+                    self.advance();
+                    Some(OrderDirection::Asc)
                 }
-                // Add the parsed expression (with optional order operator) to the orderby list
-                orderby.push(expr);
+                Keyword(Keyword::Desc) => {
+                    self.advance();
+                    Some(OrderDirection::Desc)
+                }
+                _ => None,
+            };
 
-                // If the next token is NOT a comma, we will break out of the loop (end of ORDER BY clause)
-                if !self.match_token(Comma) { break; }
+            // After the direction there can be NULLS FIRST or NULLS LAST, telling where the NULL values go
+            let nulls = if self.match_keyword(Keyword::Nulls) {
+                self.advance();
+                match self.advance() {
+                    Keyword(Keyword::First) => Some(NullsOrder::First),
+                    Keyword(Keyword::Last) => Some(NullsOrder::Last),
+                    other => return Err(ParseError::unexpected("FIRST or LAST after NULLS", other)),
+                }
+            } else {
+                None
+            };
+
+            // Add the parsed item to the orderby list
+            orderby.push(OrderByItem { expr, direction, nulls });
+
+            // If the next token is NOT a comma, we will break out of the loop (end of ORDER BY clause)
+            if !self.match_token(Comma) { break; }
 
-                // If there is a comma,it will advance to the next token to parse the next expression
+            // If there is a comma,it will advance to the next token to parse the next expression
+            self.advance();
+        }
+        Ok(orderby)
+    }
+
+    // The window of a window function, the part after OVER: (PARTITION BY dept ORDER BY salary DESC).
+    // Both parts are optional, so OVER () is a window over all rows
+    pub(crate) fn parse_window(&mut self) -> Result<WindowSpec, ParseError> {
+        self.expect_token_any_line(Token::LeftParentheses)?;
+        let mut partition_by = Vec::new();
+        if self.match_keyword(Keyword::Partition) {
+            self.advance();
+            self.expect_keyword_any_line(Keyword::By)?;
+            loop {
+                partition_by.push(parse_expression(self)?);
+                if !self.match_token(Comma) { break; }
                 self.advance();
             }
         }
-        // This line is calling the expect_semicolon() method, which checks if the next token is a semicolon (;)
-        // If we miss the semicolon at the end, it will return error in return
-        self.expect_semicolon()?;
+        let mut order_by = Vec::new();
+        if self.match_keyword(Keyword::Order) {
+            self.advance();
+            self.expect_keyword_any_line(Keyword::By)?;
+            order_by = self.parse_order_by_list()?;
+        }
+        match self.advance() {
+            RightParentheses => Ok(WindowSpec { partition_by, order_by }),
+            other => Err(ParseError::unexpected("PARTITION BY, ORDER BY or ')' in OVER (...)", other)),
+        }
+    }
 
-        // Return the parsed SELECT statement, including the columns, FROM clause, optional WHERE clause,
-        // and the ORDER BY expressions collected above
-        Ok(Statement::Select { columns, from, r#where, orderby })
+    // Fails if the next token starts a SELECT clause, see the end of parse_simple_select. GROUP and ORDER can be names,
+    // so they only count when BY comes after them
+    fn check_clause_order(&self, last: &str) -> Result<(), ParseError> {
+        let clause = match (self.peek(), self.peek_at(1)) {
+            (Keyword(Keyword::Where), _) => "WHERE",
+            (Keyword(Keyword::Group), Keyword(Keyword::By)) => "GROUP BY",
+            (Keyword(Keyword::Order), Keyword(Keyword::By)) => "ORDER BY",
+            (Keyword(Keyword::Limit), _) => "LIMIT",
+            _ => return Ok(()),
+        };
+        Err(ParseError::MisplacedClause { clause: clause.to_string(), after: last.to_string(), span: None })
+    }
+
+    // LIMIT count [OFFSET offset], or in MySQL also LIMIT offset, count (the offset comes first there)
+    fn parse_limit(&mut self) -> Result<Option<Limit>, ParseError> {
+        if !self.match_keyword(Keyword::Limit) {
+            return Ok(None);
+        }
+        self.advance();
+        let count = parse_expression(self)?;
+        if self.match_token(Comma) {
+            if !self.options.dialect.supports_limit_comma() {
+                return Err(ParseError::unexpected("OFFSET after the LIMIT count (LIMIT offset, count is MySQL syntax)", self.peek()));
+            }
+            self.advance();
+            let offset = Some(count);
+            let count = parse_expression(self)?;
+            return Ok(Some(Limit { count, offset }));
+        }
+        let offset = if self.match_keyword(Keyword::Offset) {
+            self.advance();
+            Some(parse_expression(self)?)
+        } else {
+            None
+        };
+        Ok(Some(Limit { count, offset }))
     }
 
-    fn parse_create(&mut self) -> Result<Statement, String> {
+    fn parse_create(&mut self) -> Result<Statement, ParseError> {
         // We start by expecting the CREATE keyword and then the TABLE keyword.
         // The expect_keyword() method checks if the current token matches the expected keyword
         self.expect_keyword_any_line(Keyword::Create)?;
-        self.expect_keyword_any_line(Keyword::Table)?;
+        match self.peek() {
+            Keyword(Keyword::Table) => {
+                self.advance();
+            }
+            Keyword(Keyword::Index | Keyword::Unique) => return self.parse_create_index(),
+            other => return Err(ParseError::unexpected("TABLE, INDEX or UNIQUE INDEX after CREATE", other)),
+        }
+
+        // IF NOT EXISTS means the statement does nothing when the table is already there
+        let if_not_exists = self.match_keyword(Keyword::If);
+        if if_not_exists {
+            self.advance();
+            self.expect_keyword_any_line(Keyword::Not)?;
+            self.expect_keyword_any_line(Keyword::Exists)?;
+        }
 
         // After CREATE TABLE, the next token should be the table name
-        let table_name = match self.advance() {
-            // If that token is an Identifier, it gets the table name (which is a string) and clones it
-            Identifier(name) => name.clone(),
-            // If the next token is not an Identifier (i.e., not a valid table name),
-            // we return an error saying "Expected table name after CREATE TABLE."
-            _ => return Err("Expected table name after CREATE TABLE".to_string()),
-        };
+        // If the next token is not an Identifier (i.e., not a valid table name),
+        // we return an error saying "Expected table name after CREATE TABLE."
+        let table_name = self.parse_table_name("table name after CREATE TABLE")?;
 
-        // After the table name, we expect an opening parenthesis ( to start the list of column definitions
-        self.expect_token_any_line(Token::LeftParentheses)?;
+        // CREATE TABLE name AS SELECT ... creates the table from the result of a query, so there is no column list.
+        // The SELECT is parsed as usual (with its WHERE, ORDER BY and the final semicolon)
+        if self.match_keyword(Keyword::As) {
+            self.advance();
+            if !matches!(self.peek(), Keyword(Keyword::Select | Keyword::With)) {
+                return Err(ParseError::unexpected("SELECT after AS", self.peek()));
+            }
+            let query = self.parse_select()?;
+            return Ok(Statement::CreateTableAs { table_name, if_not_exists, query: Box::new(query) });
+        }
+
+        // Otherwise, we expect an opening parenthesis ( to start the list of column definitions
+        if !self.match_token(Token::LeftParentheses) {
+            return Err(ParseError::unexpected("'(' or AS after table name", self.peek()));
+        }
+        self.advance();
 
         // We enter a loop to parse each column definition- The column name is stored in the column_name variable
+        // Table-level constraints (like PRIMARY KEY (a, b)) can be mixed into the same list, they are collected separately
         let mut column_list = Vec::new();
+        let mut table_constraints = Vec::new();
         loop {
-            // If we see a closing parenthesis, it means we've reached the end of the column list
+            // A closing parenthesis here comes right after '(' (an empty list, reported below) or after a comma
             if self.match_token(Token::RightParentheses) {
-                break; // do not consume here, handled below
-            }
-
-            // The next token should be a column name (an identifier)
-            let column_name = match self.advance() {
-                Identifier(name) => name.clone(),
-                _ => return Err("Expected column name".to_string()),
-            };
-
-            // Then parse the column type, e.g., INT, BOOL, or VARCHAR with a length
-            let column_type = match self.advance() {
-                Keyword(Keyword::Int) => DBType::Int,
-                Keyword(Keyword::Bool) => DBType::Bool,
-                Keyword(Keyword::Varchar) => {
-                    // For VARCHAR, allow parentheses with a length number inside or default length
-                    if self.match_token(Token::LeftParentheses) {
-                        self.expect_token_any_line(Token::LeftParentheses)?;
-                        let len = match self.advance() {
-                            Number(n) => *n as usize,
-                            _ => return Err("Expected number in VARCHAR(n)".to_string()),
-                        };
-                        self.expect_token_any_line(Token::RightParentheses)?;
-                        DBType::Varchar(len)
-                    } else {
-                        DBType::Varchar(255) // default length if unspecified
-                    }
+                if (!column_list.is_empty() || !table_constraints.is_empty()) && !self.options.allow_trailing_comma {
+                    return Err(ParseError::trailing_comma(self.peek()));
                 }
-                // If the token is not a valid column type, we return an error saying "Expected column type"
-                _ => return Err("Expected column type (INT, BOOL, VARCHAR)".to_string()),
-            };
+                self.advance();
+                break;
+            }
 
-            // After parsing the column type, we check if there are any constraints associated with
-            // the column, like NOT NULL, PRIMARY KEY, or CHECK.
-            let mut constraints = Vec::new();
-            loop {
+            // A column definition always starts with its name, so if the item starts with one of these keywords
+            // instead, it is a table-level constraint. CHECK can also be a column name (check INT),
+            // so it only starts a constraint when it is followed by '('
+            if self.is_table_constraint_start() {
+                table_constraints.push(self.parse_table_constraint()?);
                 match self.peek() {
-                    Keyword(Keyword::Not) => {
-                        // If we encounter the NOT NULL constraint, we add Constraint::NotNull to the list
-                        self.advance();
-                        self.expect_keyword_any_line(Keyword::Null)?;
-                        constraints.push(Constraint::NotNull);
-                    }
-                    Keyword(Keyword::Primary) => {
-                        /// If we encounter PRIMARY KEY, we add Constraint::PrimaryKey to the list
-                        self.advance();
-                        self.expect_keyword_any_line(Keyword::Key)?;
-                        constraints.push(Constraint::PrimaryKey);
-                    }
-                    Keyword(Keyword::Check) => {
-                        // If we encounter a CHECK constraint, we parse an expression for the check condition and add Constraint::Check to the list
-                        self.advance();
-                        self.expect_token_any_line(Token::LeftParentheses)?;
-                        let expr = parse_expression(self)?;
-                        self.expect_token_any_line(Token::RightParentheses)?;
-                        constraints.push(Constraint::Check(expr));
-                    }
-                    _ => break, // If no constraints are found, we break out of the loop
+                    Comma => { self.advance(); },
+                    Token::RightParentheses => {
+                        self.advance(); // consume ')'
+                        break;
+                    },
+                    _ => return Err(ParseError::unexpected("',' or ')' in column definition list", self.peek())),
                 }
+                continue;
             }
 
-            // After parsing the column name, type, and constraints, we create a TableColumn and add it to the column_list
-            column_list.push(TableColumn { column_name, column_type, constraints });
+            // Each column definition is a name, a type and optional constraints, like: id INT PRIMARY KEY
+            column_list.push(self.parse_column_definition()?);
 
             // After each column definition, we expect either a comma (,) to separate columns or a closing parenthesis ())
             // If we encounter something else, we return an error saying that we expected either a comma or a closing parenthesis.
@@ -227,48 +658,514 @@ impl Parser {
                     self.advance(); // consume ')'
                     break;
                 },
-                _ => return Err("Expected ',' or ')' in column definition list".to_string()),
+                _ => return Err(ParseError::unexpected("',' or ')' in column definition list", self.peek())),
+            }
+        }
+        // A table needs at least one column, table constraints alone (or an empty list) are not enough
+        if column_list.is_empty() {
+            return Err(ParseError::NoColumns { table: table_name.qualified_name() });
+        }
+        // Two columns with the same name can't be told apart, so this is an error even though each definition is fine
+        for (i, column) in column_list.iter().enumerate() {
+            if column_list[..i].iter().any(|other| other.column_name.eq_ignore_ascii_case(&column.column_name)) {
+                return Err(ParseError::DuplicateColumn { column: column.column_name.clone() });
             }
         }
         // After finishing the column definitions, we expect the SQL statement to end with a semicolon (;)
         self.expect_semicolon()?;
         // If everything goes correctly, it returns a CreateTable statement
-        Ok(Statement::CreateTable { table_name, column_list })
+        Ok(Statement::CreateTable { table_name, if_not_exists, column_list, table_constraints })
     }
 
+    // CREATE [UNIQUE] INDEX name ON table (a, b DESC); (the CREATE is already consumed)
+    // The columns are written like ORDER BY items, so every one of them can have its own direction
+    fn parse_create_index(&mut self) -> Result<Statement, ParseError> {
+        let unique = self.match_keyword(Keyword::Unique);
+        if unique {
+            self.advance();
+        }
+        self.expect_keyword_any_line(Keyword::Index)?;
+        let name = self.expect_identifier("index name after CREATE INDEX")?;
+        self.expect_keyword_any_line(Keyword::On)?;
+        let table = self.parse_table_name("table name after ON")?;
+        self.expect_token_any_line(Token::LeftParentheses)?;
+        let columns = self.parse_order_by_list()?;
+        self.expect_token_any_line(Token::RightParentheses)?;
+        self.expect_semicolon()?;
+        Ok(Statement::CreateIndex { name, table, columns, unique })
+    }
 
-    // The expect_token_any_line function checks if the next token matches the expected token type,
-    // regardless of whether the formatting includes newlines or spaces between tokens
-    //expected: The token we’re expecting (e.g., LeftParentheses, Comma, Identifier, etc.)
-    fn expect_token_any_line(&mut self, expected: Token) -> Result<(), String> {
-        if self.match_token(expected.clone()) {
+    // ALTER TABLE changes an existing table, for now we support adding and dropping one column:
+    // ALTER TABLE t ADD COLUMN name INT NOT NULL; or ALTER TABLE t DROP COLUMN name;
+    // The COLUMN keyword can be left out, like in most databases
+    fn parse_alter(&mut self) -> Result<Statement, ParseError> {
+        self.expect_keyword_any_line(Keyword::Alter)?;
+        self.expect_keyword_any_line(Keyword::Table)?;
+
+        let table_name = self.parse_table_name("table name after ALTER TABLE")?;
+
+        let action = match self.advance() {
+            Keyword(Keyword::Add) => {
+                if self.match_keyword(Keyword::Column) {
+                    self.advance();
+                }
+                // The new column is written exactly like in CREATE TABLE, so constraints work the same way
+                AlterTableAction::AddColumn(self.parse_column_definition()?)
+            }
+            Keyword(Keyword::Drop) => {
+                if self.match_keyword(Keyword::Column) {
+                    self.advance();
+                }
+                let name = self.expect_identifier("column name after DROP COLUMN")?;
+                // The same keywords as in ON DELETE CASCADE, but here they are about what depends on the column
+                let drop_behavior = match self.peek() {
+                    Keyword(Keyword::Cascade) => Some(DropBehavior::Cascade),
+                    Keyword(Keyword::Restrict) => Some(DropBehavior::Restrict),
+                    _ => None,
+                };
+                if drop_behavior.is_some() {
+                    self.advance();
+                }
+                AlterTableAction::DropColumn { name, drop_behavior }
+            }
+            other => return Err(ParseError::unexpected("ADD or DROP after ALTER TABLE", other)),
+        };
+
+        self.expect_semicolon()?;
+        Ok(Statement::AlterTable { table_name, action })
+    }
+
+    // TRUNCATE TABLE t; removes all rows of a table. The TABLE keyword is optional, TRUNCATE t; works too
+    fn parse_truncate(&mut self) -> Result<Statement, ParseError> {
+        self.expect_keyword_any_line(Keyword::Truncate)?;
+        if self.match_keyword(Keyword::Table) {
             self.advance();
-            Ok(())
+        }
+        let table_name = self.parse_table_name("table name after TRUNCATE")?;
+        self.expect_semicolon()?;
+        Ok(Statement::Truncate { table_name })
+    }
+
+    // INSERT INTO t (a, b) VALUES (1, 2), (3, 4); adds rows to a table. The column names are optional,
+    // without them the values are for all columns of the table, in their order
+    fn parse_insert(&mut self) -> Result<Statement, ParseError> {
+        self.expect_keyword_any_line(Keyword::Insert)?;
+        self.expect_keyword_any_line(Keyword::Into)?;
+        let table_name = self.parse_table_name("table name after INSERT INTO")?;
+        let columns = if self.match_token(Token::LeftParentheses) {
+            self.parse_column_name_list()?
         } else {
-            Err(format!("Expected token {:?}, got {:?}", expected, self.peek()))
+            Vec::new()
+        };
+        self.expect_keyword_any_line(Keyword::Values)?;
+        let mut values: Vec<Vec<Expression>> = Vec::new();
+        loop {
+            let row = self.parse_values_row()?;
+            // Every row must fill the listed columns, or have as many values as the first row when they aren't listed
+            let expected = values.first().map_or(columns.len(), Vec::len);
+            if expected > 0 && row.len() != expected {
+                return Err(ParseError::ValueCountMismatch { row: values.len(), expected, found: row.len() });
+            }
+            values.push(row);
+            if !self.match_token(Comma) {
+                break;
+            }
+            self.advance();
         }
+        let returning = self.parse_returning()?;
+        self.expect_semicolon()?;
+        Ok(Statement::Insert { table_name, columns, values, returning })
     }
 
-    // The expect_keyword_any_line function checks if the next token is the expected keyword,
-    // regardless of whether it's on a new line or the same line.
-    fn expect_keyword_any_line(&mut self, kw: Keyword) -> Result<(), String> {
-        if self.match_keyword(kw.clone()) {
+    // One row of VALUES, the values in parentheses: (1, 'a', NULL)
+    fn parse_values_row(&mut self) -> Result<Vec<Expression>, ParseError> {
+        self.expect_token_any_line(Token::LeftParentheses)?;
+        let mut row = Vec::new();
+        loop {
+            // DEFAULT takes the place of a value, the column then gets its default value
+            if self.match_keyword(Keyword::Default) {
+                self.advance();
+                row.push(Expression::Default);
+            } else {
+                row.push(parse_expression(self)?);
+            }
+            match self.advance() {
+                Comma => {}
+                Token::RightParentheses => break,
+                other => return Err(ParseError::unexpected("',' or ')' in VALUES", other)),
+            }
+            if self.match_token(Token::RightParentheses) {
+                if !self.options.allow_trailing_comma {
+                    return Err(ParseError::trailing_comma(self.peek()));
+                }
+                self.advance();
+                break;
+            }
+        }
+        Ok(row)
+    }
+
+    // UPDATE t SET a = 1, b = b + 1 WHERE id = 5; changes the rows that match WHERE (all of them without it)
+    fn parse_update(&mut self) -> Result<Statement, ParseError> {
+        self.expect_keyword_any_line(Keyword::Update)?;
+        let table_name = self.parse_table_name("table name after UPDATE")?;
+        self.expect_keyword_any_line(Keyword::Set)?;
+        let mut assignments = Vec::new();
+        loop {
+            let column = self.expect_identifier("column name in SET")?;
+            self.expect_token_any_line(Token::Equal)?;
+            let value = parse_expression(self)?;
+            assignments.push(Assignment { column, value });
+            if !self.match_token(Comma) {
+                break;
+            }
+            self.advance();
+        }
+        let r#where = self.parse_where()?;
+        let returning = self.parse_returning()?;
+        self.expect_semicolon()?;
+        Ok(Statement::Update { table_name, assignments, r#where, returning })
+    }
+
+    // DELETE FROM t WHERE id = 5; removes the rows that match WHERE (all of them without it)
+    fn parse_delete(&mut self) -> Result<Statement, ParseError> {
+        self.expect_keyword_any_line(Keyword::Delete)?;
+        self.expect_keyword_any_line(Keyword::From)?;
+        let table_name = self.parse_table_name("table name after DELETE FROM")?;
+        let r#where = self.parse_where()?;
+        let returning = self.parse_returning()?;
+        self.expect_semicolon()?;
+        Ok(Statement::Delete { table_name, r#where, returning })
+    }
+
+    // Parses one column definition: the column name, its type and all constraints written after it
+    // It is used by CREATE TABLE for every column in the list, and by ALTER TABLE ... ADD COLUMN
+    fn parse_column_definition(&mut self) -> Result<TableColumn, ParseError> {
+        // The next token should be a column name (an identifier)
+        let column_name = self.expect_identifier("column name")?;
+
+        // Then parse the column type, e.g., INT, BOOL, FLOAT, or VARCHAR with a length
+        let column_type = self.parse_column_type()?;
+
+        // After parsing the column type, we check if there are any constraints associated with
+        // the column, like NOT NULL, PRIMARY KEY, UNIQUE, CHECK, DEFAULT or REFERENCES (and COLLATE, CHARACTER SET or GENERATED,
+        // which aren't really constraints, but they are written in the same place and in any order with them).
+        let mut constraints = Vec::new();
+        loop {
+            match self.peek() {
+                Keyword(Keyword::Not) => {
+                    // If we encounter the NOT NULL constraint, we add Constraint::NotNull to the list
+                    self.advance();
+                    self.expect_keyword_any_line(Keyword::Null)?;
+                    constraints.push(Constraint::NotNull);
+                }
+                Keyword(Keyword::Primary) => {
+                    // If we encounter PRIMARY KEY, we add Constraint::PrimaryKey to the list
+                    self.advance();
+                    self.expect_keyword_any_line(Keyword::Key)?;
+                    constraints.push(Constraint::PrimaryKey);
+                }
+                Keyword(Keyword::Unique) => {
+                    // UNIQUE is a single keyword without arguments, so we just add Constraint::Unique to the list
+                    self.advance();
+                    constraints.push(Constraint::Unique);
+                }
+                Keyword(Keyword::Check) => {
+                    // If we encounter a CHECK constraint, we parse an expression for the check condition and add Constraint::Check to the list
+                    self.advance();
+                    constraints.push(Constraint::Check(self.parse_check_condition()?));
+                }
+                Keyword(Keyword::Default) => {
+                    // DEFAULT is followed by the value the column gets when a new row doesn't set it, like DEFAULT 0 or DEFAULT NULL
+                    self.advance();
+                    constraints.push(Constraint::Default(parse_expression(self)?));
+                }
+                Keyword(Keyword::References) => {
+                    // If we encounter REFERENCES, the next token must be the name of the referenced table (maybe with its schema)
+                    self.advance();
+                    let table = self.parse_table_name("table name after REFERENCES")?;
+                    // The referenced column is optional and written in parentheses, like REFERENCES users(id)
+                    let column = if self.match_token(Token::LeftParentheses) {
+                        self.advance();
+                        let column = self.expect_identifier(&format!("column name in REFERENCES {}(...)", table))?;
+                        self.expect_token_any_line(Token::RightParentheses)?;
+                        Some(column)
+                    } else {
+                        None
+                    };
+                    let (on_delete, on_update) = self.parse_referential_actions()?;
+                    constraints.push(Constraint::References { table, column, on_delete, on_update });
+                }
+                Keyword(Keyword::Collate) => {
+                    // COLLATE is followed by the name of the collation, like utf8_general_ci or "C"
+                    self.advance();
+                    constraints.push(Constraint::Collate(self.expect_identifier("collation name after COLLATE")?));
+                }
+                Keyword(Keyword::Generated) => {
+                    // GENERATED ALWAYS AS (expr) computes the value of the column from the other columns of the row,
+                    // STORED keeps the computed value on disk, VIRTUAL (the same as writing nothing) computes it when it is read
+                    self.advance();
+                    self.expect_keyword_any_line(Keyword::Always)?;
+                    self.expect_keyword_any_line(Keyword::As)?;
+                    self.expect_token_any_line(Token::LeftParentheses)?;
+                    let expr = parse_expression(self)?;
+                    self.expect_token_any_line(Token::RightParentheses)?;
+                    let stored = self.match_keyword(Keyword::Stored);
+                    if stored || self.match_keyword(Keyword::Virtual) {
+                        self.advance();
+                    }
+                    constraints.push(Constraint::Generated { expr, stored });
+                }
+                Keyword(Keyword::Character) => {
+                    // CHARACTER SET is followed by the name of the character set, like utf8mb4
+                    self.advance();
+                    self.expect_keyword_any_line(Keyword::Set)?;
+                    constraints.push(Constraint::CharacterSet(self.expect_identifier("character set name after CHARACTER SET")?));
+                }
+                _ => break, // If no constraints are found, we break out of the loop
+            }
+        }
+
+        // After parsing the column name, type, and constraints, we create a TableColumn
+        Ok(TableColumn { column_name, column_type, constraints })
+    }
+
+    // The (n) after VARCHAR or CHAR. A column that can't hold a single character makes no sense,
+    // and a huge length is almost always a typo, so both are rejected
+    fn parse_char_length(&mut self, type_name: &str) -> Result<usize, ParseError> {
+        self.expect_token_any_line(Token::LeftParentheses)?;
+        let length = match self.advance() {
+            Number(n) => *n,
+            other => return Err(ParseError::unexpected(format!("number in {}(n)", type_name), other)),
+        };
+        let max = self.options.max_char_length;
+        if length == 0 || max.is_some_and(|max| length > max as u64) {
+            return Err(ParseError::InvalidLength { type_name: type_name.to_string(), length, max });
+        }
+        self.expect_token_any_line(Token::RightParentheses)?;
+        Ok(length as usize)
+    }
+
+    // Parses a column type, e.g., INT, BOOL, FLOAT, or VARCHAR with a length
+    pub(crate) fn parse_column_type(&mut self) -> Result<DBType, ParseError> {
+        let column_type = match self.advance() {
+            Keyword(Keyword::Int) => DBType::Int,
+            Keyword(Keyword::Bool) => DBType::Bool,
+            Keyword(Keyword::Varchar) => {
+                // For VARCHAR, allow parentheses with a length number inside or default length
+                if self.match_token(Token::LeftParentheses) {
+                    DBType::Varchar(self.parse_char_length("VARCHAR")?)
+                } else {
+                    DBType::Varchar(255) // default length if unspecified
+                }
+            }
+            Keyword(Keyword::Char) => {
+                // CHAR takes its length the same way as VARCHAR, but without it, it is a single character
+                if self.match_token(Token::LeftParentheses) {
+                    DBType::Char(self.parse_char_length("CHAR")?)
+                } else {
+                    DBType::Char(1) // CHAR alone means CHAR(1)
+                }
+            }
+            Keyword(Keyword::Text) => DBType::Text,
+            Keyword(Keyword::Float) => DBType::Float,
+            Keyword(Keyword::Double) => DBType::Double,
+            Keyword(Keyword::Decimal) => {
+                // DECIMAL can be written alone, as DECIMAL(p) or as DECIMAL(p, s)
+                let mut precision = None;
+                let mut scale = None;
+                if self.match_token(Token::LeftParentheses) {
+                    self.expect_token_any_line(Token::LeftParentheses)?;
+                    let p = match self.advance() {
+                        Number(n) => *n,
+                        other => return Err(ParseError::unexpected("precision in DECIMAL(p, s)", other)),
+                    };
+                    let s = if self.match_token(Comma) {
+                        self.advance();
+                        match self.advance() {
+                            Number(n) => Some(*n),
+                            other => return Err(ParseError::unexpected("scale in DECIMAL(p, s)", other)),
+                        }
+                    } else {
+                        None
+                    };
+                    // Both have to fit into u32, and there can't be more digits after the point than there are digits at all
+                    let invalid = || ParseError::InvalidDecimal { precision: p, scale: s };
+                    precision = Some(u32::try_from(p).map_err(|_| invalid())?);
+                    scale = s.map(u32::try_from).transpose().map_err(|_| invalid())?;
+                    if s.is_some_and(|s| s > p) {
+                        return Err(invalid());
+                    }
+                    self.expect_token_any_line(Token::RightParentheses)?;
+                }
+                DBType::Decimal { precision, scale }
+            }
+            Keyword(Keyword::Date) => DBType::Date,
+            Keyword(Keyword::Time) => DBType::Time,
+            Keyword(Keyword::Timestamp) => DBType::Timestamp,
+            // If the token is not a valid column type, we return an error saying "Expected column type"
+            other => return Err(ParseError::unexpected("column type (INT, BOOL, VARCHAR, CHAR, TEXT, FLOAT, DOUBLE, DECIMAL, DATE, TIME, TIMESTAMP)", other)),
+        };
+        Ok(column_type)
+    }
+
+    // What happens to a row when the row it references is deleted or updated: ON DELETE CASCADE ON UPDATE SET NULL
+    // Both are optional and can be written in any order, but each of them only once
+    fn parse_referential_actions(&mut self) -> Result<(Option<RefAction>, Option<RefAction>), ParseError> {
+        let mut on_delete = None;
+        let mut on_update = None;
+        while *self.peek() == Keyword(Keyword::On) {
+            self.advance();
+            let (event, action) = match self.advance() {
+                Keyword(Keyword::Delete) if on_delete.is_none() => ("DELETE", &mut on_delete),
+                Keyword(Keyword::Update) if on_update.is_none() => ("UPDATE", &mut on_update),
+                other => return Err(ParseError::unexpected("DELETE or UPDATE after ON (each of them can be written once)", other)),
+            };
+            let context = format!("CASCADE, SET NULL, SET DEFAULT, RESTRICT or NO ACTION after ON {}", event);
+            *action = Some(match self.advance() {
+                Keyword(Keyword::Cascade) => RefAction::Cascade,
+                Keyword(Keyword::Restrict) => RefAction::Restrict,
+                Keyword(Keyword::Set) => match self.advance() {
+                    Keyword(Keyword::Null) => RefAction::SetNull,
+                    Keyword(Keyword::Default) => RefAction::SetDefault,
+                    other => return Err(ParseError::unexpected("NULL or DEFAULT after SET", other)),
+                },
+                Keyword(Keyword::No) => {
+                    self.expect_keyword_any_line(Keyword::Action)?;
+                    RefAction::NoAction
+                }
+                other => return Err(ParseError::unexpected(context, other)),
+            });
+        }
+        Ok((on_delete, on_update))
+    }
+
+    // Parses one table-level constraint from the CREATE TABLE list, for example:
+    // PRIMARY KEY (a, b), UNIQUE (email), CHECK (a < b) or FOREIGN KEY (user_id) REFERENCES users (id)
+    fn parse_table_constraint(&mut self) -> Result<TableConstraint, ParseError> {
+        match self.advance() {
+            Keyword(Keyword::Primary) => {
+                self.expect_keyword_any_line(Keyword::Key)?;
+                Ok(TableConstraint::PrimaryKey(self.parse_column_name_list()?))
+            }
+            Keyword(Keyword::Unique) => Ok(TableConstraint::Unique(self.parse_column_name_list()?)),
+            Keyword(Keyword::Check) => Ok(TableConstraint::Check(self.parse_check_condition()?)),
+            Keyword(Keyword::Foreign) => {
+                self.expect_keyword_any_line(Keyword::Key)?;
+                let columns = self.parse_column_name_list()?;
+                self.expect_keyword_any_line(Keyword::References)?;
+                let table = self.parse_table_name("table name after REFERENCES")?;
+                // Just like with the column constraint, the referenced columns are optional
+                let referenced_columns = if self.match_token(Token::LeftParentheses) {
+                    self.parse_column_name_list()?
+                } else {
+                    Vec::new()
+                };
+                let (on_delete, on_update) = self.parse_referential_actions()?;
+                Ok(TableConstraint::ForeignKey { columns, table, referenced_columns, on_delete, on_update })
+            }
+            other => Err(ParseError::unexpected("table constraint", other)),
+        }
+    }
+
+    // Parses a parenthesized, comma separated list of column names like (a, b, c)
+    // The list can't be empty, because a constraint over zero columns makes no sense
+    fn parse_column_name_list(&mut self) -> Result<Vec<String>, ParseError> {
+        self.expect_token_any_line(Token::LeftParentheses)?;
+        let mut names = Vec::new();
+        loop {
+            names.push(self.expect_identifier("column name")?);
+            match self.advance() {
+                Comma => continue,
+                Token::RightParentheses => break,
+                other => return Err(ParseError::unexpected("',' or ')' in column name list", other)),
+            }
+        }
+        Ok(names)
+    }
+
+
+    // All the expect_ functions go through here: if the next token is accepted by `pred`, it is consumed,
+    // otherwise the error says what was expected (`describe`) and which token was found instead.
+    // That way every "Expected X, found Y" error is built the same way
+    fn expect(&mut self, pred: impl Fn(&Token<'a>) -> bool, describe: &str) -> Result<(), ParseError> {
+        if pred(self.peek()) {
             self.advance();
             Ok(())
         } else {
-            Err(format!("Expected keyword {:?}, got {:?}", kw, self.peek()))
+            Err(ParseError::unexpected(describe, self.peek()))
         }
     }
 
-    //Here it checks if the next token is a semicolon; advances if yes, otherwise returns an error.
-    fn expect_semicolon(&mut self) -> Result<(), String> {
+    // The expect_token_any_line function checks if the next token matches the expected token type,
+    // regardless of whether the formatting includes newlines or spaces between tokens
+    //expected: The token we’re expecting (e.g., LeftParentheses, Comma, Identifier, etc.)
+    fn expect_token_any_line(&mut self, expected: Token<'_>) -> Result<(), ParseError> {
+        let kind = std::mem::discriminant(&expected);
+        self.expect(|token| std::mem::discriminant(token) == kind, &expected.to_string())
+    }
+
+    // The expect_keyword_any_line function checks if the next token is the expected keyword,
+    // regardless of whether it's on a new line or the same line.
+    fn expect_keyword_any_line(&mut self, kw: Keyword) -> Result<(), ParseError> {
+        self.expect(|token| matches!(token, Keyword(k) if *k == kw), &kw.to_string())
+    }
+
+    // Parses the (condition) part of a CHECK constraint, the CHECK keyword is already consumed.
+    // A check has to be a condition, so something like CHECK (name) or CHECK (5) is an error already here
+    fn parse_check_condition(&mut self) -> Result<Expression, ParseError> {
+        self.expect_token_any_line(Token::LeftParentheses)?;
+        let expr = parse_expression(self)?;
+        self.expect_token_any_line(Token::RightParentheses)?;
+        if !is_condition(&expr) {
+            return Err(ParseError::InvalidCheck { expression: expr.to_string() });
+        }
+        Ok(expr)
+    }
+
+    // Decides if the next item in a CREATE TABLE list is a table constraint rather than a column definition
+    fn is_table_constraint_start(&self) -> bool {
         match self.peek() {
-            Semicolon => {
-                self.advance();
-                Ok(())
+            Keyword(Keyword::Primary | Keyword::Unique | Keyword::Foreign) => true,
+            Keyword(Keyword::Check) => {
+                !self.non_reserved.contains(&Keyword::Check) || matches!(self.peek_at(1), Token::LeftParentheses)
             }
-            _ => Err(format!("Expected semicolon, found {:?}", self.peek())),
+            _ => false,
+        }
+    }
+
+    // Reads the next token as a name (of a table, a column, ...) and returns it
+    // `context` describes what we wanted, like "table name after FROM", so every place reports the error the same way
+    // Non-reserved keywords are accepted here too, so a column can be called `key` or `order`
+    fn expect_identifier(&mut self, context: &str) -> Result<String, ParseError> {
+        if let Some(name) = self.keyword_as_identifier(self.peek()) {
+            self.advance();
+            return Ok(name);
+        }
+        match self.advance() {
+            Identifier(name) => Ok(name.to_string()),
+            other => Err(ParseError::unexpected(context, other)),
+        }
+    }
+
+    // A table name with an optional schema in front of it: users or public.users
+    fn parse_table_name(&mut self, context: &str) -> Result<TableName, ParseError> {
+        let name = self.expect_identifier(context)?;
+        if *self.peek() != Dot {
+            return Ok(TableName { schema: None, name });
         }
+        self.advance();
+        let table = self.expect_identifier("table name after the schema name and '.'")?;
+        Ok(TableName { schema: Some(name), name: table })
+    }
+
+    //Here it checks if the next token is a semicolon; advances if yes, otherwise returns an error.
+    //If semicolons aren't required, the end of the input is also fine (only the last statement can end like that)
+    fn expect_semicolon(&mut self) -> Result<(), ParseError> {
+        if *self.peek() == Eof && !self.options.require_semicolon {
+            return Ok(());
+        }
+        self.expect(|token| *token == Semicolon, "semicolon")
     }
 
     ///It returns true if the next token is the keyword we are looking for, otherwise false
@@ -278,20 +1175,566 @@ impl Parser {
 
     // Checks if the next token is of the same type as the expected token
     // Useful for matching token categories like any Identifier or Keyword.
-    fn match_token(&self, expected: Token) -> bool {
+    fn match_token(&self, expected: Token<'_>) -> bool {
         std::mem::discriminant(self.peek()) == std::mem::discriminant(&expected)
     }
 
+    // Called before going one level deeper into a nested expression or subquery, fails when it would be too deep.
+    // Every successful call has to be matched with leave_nesting() when that level is finished (also on errors).
+    // The outermost expression isn't nested in anything, so it doesn't count: max_depth parentheses around a column still parse
+    pub(crate) fn enter_nesting(&mut self) -> Result<(), ParseError> {
+        if self.depth > self.max_depth {
+            return Err(ParseError::NestingTooDeep { max_depth: self.max_depth });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn leave_nesting(&mut self) {
+        self.depth -= 1;
+    }
+
+    // Where the current token (the one peek() returns) is in the input, if the parser knows the spans
+    // At the end of the input it is an empty span right after the last token
+    pub fn current_span(&self) -> Option<Span> {
+        self.span_at(self.current)
+    }
+
+    fn span_at(&self, index: usize) -> Option<Span> {
+        match self.spans.get(index) {
+            Some(span) => Some(*span),
+            None => self.spans.last().map(|last| Span { start: last.end, end: last.end }),
+        }
+    }
+
+    // Finds the token an error is about. Errors are made either from the token that was just consumed (advance())
+    // or from the one we only looked at (peek()), so it is one of these two. A trailing comma is the token before `before`
+    fn error_span(&self, error: &ParseError) -> Option<Span> {
+        let (found, offset) = match error {
+            ParseError::UnexpectedToken { found, .. } => (found, 0),
+            ParseError::TrailingComma { before, .. } => (before, 1),
+            ParseError::UnexpectedEof { .. } => {
+                return self.span_at(self.tokens.iter().position(|token| *token == Eof).unwrap_or(self.tokens.len()));
+            }
+            ParseError::EmptyStatement { .. } | ParseError::MisplacedClause { .. } => return self.current_span(),
+            _ => return None,
+        };
+        let index = [self.current.checked_sub(1), Some(self.current)]
+            .into_iter()
+            .flatten()
+            .find(|&index| self.tokens.get(index) == Some(found))?;
+        self.span_at(index.checked_sub(offset)?)
+    }
+
+    // The index of the current token, it can be given to reset_to() later to go back to this token
+    pub fn position(&self) -> usize {
+        self.current
+    }
+
+    // Goes back (or forward) to a position returned by position(), for trying one way of parsing and backtracking
+    // if it fails. A position past the end is the end of the input
+    pub fn reset_to(&mut self, pos: usize) {
+        self.current = pos.min(self.tokens.len());
+    }
+
+    // Returns true when all statements were consumed and only the end of the input is left
+    pub fn is_at_end(&self) -> bool {
+        matches!(self.peek(), Eof)
+    }
+
     // Returns a reference to the current token without advancing the parser.
     // If we are at the end of the token stream, returns an End-Of-File (Eof) token as a sentinel
-    pub(crate) fn peek(&self) -> &Token {
-        self.tokens.get(self.current).unwrap_or(&Eof)
+    pub(crate) fn peek(&self) -> &Token<'a> {
+        self.peek_at(0)
+    }
+
+    // Looks `offset` tokens ahead without advancing, peek_at(0) is the same as peek()
+    // This is needed for grammar that can only be recognized by more than one token, like IS NOT NULL
+    pub(crate) fn peek_at(&self, offset: usize) -> &Token<'a> {
+        self.tokens.get(self.current + offset).unwrap_or(&Eof)
     }
 
     //This function advances the parser to the next token and returns it
-    pub(crate) fn advance(&mut self) -> &Token {
+    pub(crate) fn advance(&mut self) -> &Token<'a> {
         let idx = self.current;
         self.current += 1;
         self.tokens.get(idx).unwrap_or(&Eof)
     }
-}
\ No newline at end of file
+}
+
+// ORDER BY 2 sorts by the second column of the SELECT list, so the number has to be one of the columns.
+// With * in the list the number of columns isn't known here, then any position is accepted
+fn check_ordinals(orderby: &[OrderByItem], columns: &[Expression]) -> Result<(), ParseError> {
+    if columns.iter().any(|column| matches!(column, Expression::AllColumns | Expression::QualifiedAllColumns(_))) {
+        return Ok(());
+    }
+    for item in orderby {
+        if let Expression::Number(ordinal) = item.expr {
+            if ordinal == 0 || ordinal > columns.len() as u64 {
+                return Err(ParseError::InvalidOrdinal { ordinal, columns: columns.len() });
+            }
+        }
+    }
+    Ok(())
+}
+
+// Tells if the expression (by its outermost part) gives a boolean: a comparison, AND/OR, NOT, EXISTS, IN, IS or TRUE/FALSE/UNKNOWN.
+// It is only a quick look at the shape, the types of the columns are not known here
+fn is_condition(expr: &Expression) -> bool {
+    match expr {
+        Expression::BinaryOperation { operator, .. } => operator.is_comparison() || operator.is_logical(),
+        Expression::UnaryOperation { operator: UnaryOperator::Not, .. } => true,
+        Expression::Exists { .. }
+        | Expression::InList { .. }
+        | Expression::InSubquery { .. }
+        | Expression::Is { .. }
+        | Expression::QuantifiedComparison { .. }
+        | Expression::Like { .. }
+        | Expression::And(_)
+        | Expression::Or(_)
+        | Expression::Bool(_)
+        | Expression::Unknown => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, tokenize, DBType, ParseError, Parser, ParserOptions, SetOp, Statement, DEFAULT_MAX_DEPTH};
+    use crate::statement::{Assignment, BinaryOperator, Constraint, Expression, RefAction, TableColumn, TableConstraint, TableName};
+    use crate::Dialect;
+    use crate::token::Token;
+
+    // The type of the only column of a CREATE TABLE
+    fn column_type(sql: &str) -> Result<DBType, ParseError> {
+        match parse(sql)? {
+            Statement::CreateTable { mut column_list, .. } => Ok(column_list.remove(0).column_type),
+            other => panic!("expected CREATE TABLE, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn deeply_nested_subqueries_are_too_deep() {
+        let nested = |n: usize| format!("SELECT a FROM t WHERE {}a = 1{};", "EXISTS (SELECT a FROM t WHERE ".repeat(n), ")".repeat(n));
+        assert!(parse(&nested(10)).is_ok());
+        assert_eq!(parse(&nested(1000)), Err(ParseError::NestingTooDeep { max_depth: DEFAULT_MAX_DEPTH }));
+    }
+
+    #[test]
+    fn decimal_precision_and_scale() {
+        assert_eq!(column_type("CREATE TABLE t (a DECIMAL);"), Ok(DBType::Decimal { precision: None, scale: None }));
+        assert_eq!(column_type("CREATE TABLE t (a DECIMAL(10));"), Ok(DBType::Decimal { precision: Some(10), scale: None }));
+        assert_eq!(column_type("CREATE TABLE t (a DECIMAL(10, 2));"), Ok(DBType::Decimal { precision: Some(10), scale: Some(2) }));
+        assert_eq!(column_type("CREATE TABLE t (a DECIMAL(2, 2));"), Ok(DBType::Decimal { precision: Some(2), scale: Some(2) }));
+    }
+
+    #[test]
+    fn decimal_values_that_dont_fit_are_errors() {
+        // 4294967297 is u32::MAX + 2, it used to be cut to 1
+        assert_eq!(
+            column_type("CREATE TABLE t (a DECIMAL(4294967297, 1));"),
+            Err(ParseError::InvalidDecimal { precision: 4294967297, scale: Some(1) })
+        );
+        assert_eq!(
+            column_type("CREATE TABLE t (a DECIMAL(10, 4294967296));"),
+            Err(ParseError::InvalidDecimal { precision: 10, scale: Some(4294967296) })
+        );
+    }
+
+    #[test]
+    fn decimal_scale_larger_than_precision() {
+        let error = column_type("CREATE TABLE t (a DECIMAL(2, 3));").unwrap_err();
+        assert_eq!(error, ParseError::InvalidDecimal { precision: 2, scale: Some(3) });
+        assert_eq!(error.to_string(), "DECIMAL scale 3 is larger than its precision 2");
+    }
+
+    #[test]
+    fn trailing_comma_in_create_table_is_an_error_by_default() {
+        let error = parse("CREATE TABLE t (a INT,);").unwrap_err();
+        assert!(matches!(error, ParseError::TrailingComma { before: Token::RightParentheses, .. }), "{:?}", error);
+    }
+
+    #[test]
+    fn trailing_comma_in_create_table_with_allow_trailing_comma() {
+        let options = ParserOptions { allow_trailing_comma: true, ..ParserOptions::default() };
+        let statement = Parser::with_options(tokenize("CREATE TABLE t (a INT,);").unwrap(), options).parse().unwrap();
+        assert_eq!(statement, parse("CREATE TABLE t (a INT);").unwrap());
+    }
+
+    #[test]
+    fn trailing_comma_in_values_is_an_error_by_default() {
+        let error = parse("INSERT INTO t (a, b) VALUES (1, 2,);").unwrap_err();
+        assert!(matches!(error, ParseError::TrailingComma { before: Token::RightParentheses, .. }), "{:?}", error);
+    }
+
+    #[test]
+    fn trailing_comma_in_values_with_allow_trailing_comma() {
+        let options = ParserOptions { allow_trailing_comma: true, ..ParserOptions::default() };
+        let parse_with_option = |sql: &str| Parser::with_options(tokenize(sql).unwrap(), options.clone()).parse();
+        // The comma doesn't add a value, so the rows still have as many values as the columns
+        assert_eq!(
+            parse_with_option("INSERT INTO t (a, b) VALUES (1, 2,), (3, DEFAULT,);").unwrap(),
+            parse("INSERT INTO t (a, b) VALUES (1, 2), (3, DEFAULT);").unwrap()
+        );
+        assert_eq!(
+            parse_with_option("INSERT INTO t (a, b) VALUES (1,);"),
+            Err(ParseError::ValueCountMismatch { row: 0, expected: 2, found: 1 })
+        );
+        // Only one comma is allowed, and only after a value
+        assert!(parse_with_option("INSERT INTO t (a) VALUES (1,,);").is_err());
+        assert!(parse_with_option("INSERT INTO t (a) VALUES (,);").is_err());
+    }
+
+    #[test]
+    fn duplicate_column_is_an_error() {
+        assert_eq!(parse("CREATE TABLE t (a INT, a INT);"), Err(ParseError::DuplicateColumn { column: "a".to_string() }));
+        assert_eq!(parse("CREATE TABLE t (a INT, b TEXT, a VARCHAR(10));"), Err(ParseError::DuplicateColumn { column: "a".to_string() }));
+    }
+
+    #[test]
+    fn duplicate_column_names_are_compared_without_case() {
+        assert_eq!(parse("CREATE TABLE t (a INT, A INT);"), Err(ParseError::DuplicateColumn { column: "A".to_string() }));
+        assert!(parse("CREATE TABLE t (a INT, ab INT);").is_ok());
+    }
+
+    fn select_from(table: &str) -> Statement {
+        parse(&format!("SELECT a FROM {}", table)).unwrap()
+    }
+
+    fn set_operation(left: Statement, op: SetOp, all: bool, right: Statement) -> Statement {
+        Statement::SetOperation { op, all, left: Box::new(left), right: Box::new(right) }
+    }
+
+    #[test]
+    fn intersect_binds_tighter_than_union_and_except() {
+        // (t UNION ALL u) EXCEPT (v INTERSECT w)
+        assert_eq!(
+            parse("SELECT a FROM t UNION ALL SELECT a FROM u EXCEPT SELECT a FROM v INTERSECT SELECT a FROM w;"),
+            Ok(set_operation(
+                set_operation(select_from("t"), SetOp::Union, true, select_from("u")),
+                SetOp::Except,
+                false,
+                set_operation(select_from("v"), SetOp::Intersect, false, select_from("w")),
+            ))
+        );
+        // (t INTERSECT u) UNION v
+        assert_eq!(
+            parse("SELECT a FROM t INTERSECT SELECT a FROM u UNION SELECT a FROM v;"),
+            Ok(set_operation(
+                set_operation(select_from("t"), SetOp::Intersect, false, select_from("u")),
+                SetOp::Union,
+                false,
+                select_from("v"),
+            ))
+        );
+    }
+
+    #[test]
+    fn set_operators_of_the_same_precedence_group_from_the_left() {
+        // (t EXCEPT u) UNION v, not t EXCEPT (u UNION v)
+        assert_eq!(
+            parse("SELECT a FROM t EXCEPT SELECT a FROM u UNION SELECT a FROM v;"),
+            Ok(set_operation(
+                set_operation(select_from("t"), SetOp::Except, false, select_from("u")),
+                SetOp::Union,
+                false,
+                select_from("v"),
+            ))
+        );
+    }
+
+    // The columns and the table constraints of a CREATE TABLE
+    fn create_table(sql: &str) -> (Vec<TableColumn>, Vec<TableConstraint>) {
+        match parse(sql).unwrap() {
+            Statement::CreateTable { column_list, table_constraints, .. } => (column_list, table_constraints),
+            other => panic!("expected CREATE TABLE, got {:?}", other),
+        }
+    }
+
+    fn a_greater_than_zero() -> Expression {
+        Expression::BinaryOperation {
+            left_operand: Box::new(Expression::Identifier("a".to_string())),
+            operator: BinaryOperator::GreaterThan,
+            right_operand: Box::new(Expression::Number(0)),
+        }
+    }
+
+    #[test]
+    fn column_constraints() {
+        let (columns, _) = create_table(
+            "CREATE TABLE t (a INT UNIQUE CHECK (a > 0), b INT NOT NULL REFERENCES users (id) ON DELETE CASCADE ON UPDATE SET NULL, c INT REFERENCES users);",
+        );
+        assert_eq!(columns[0].constraints, vec![Constraint::Unique, Constraint::Check(a_greater_than_zero())]);
+        assert_eq!(
+            columns[1].constraints,
+            vec![
+                Constraint::NotNull,
+                Constraint::References {
+                    table: TableName { schema: None, name: "users".to_string() },
+                    column: Some("id".to_string()),
+                    on_delete: Some(RefAction::Cascade),
+                    on_update: Some(RefAction::SetNull),
+                },
+            ]
+        );
+        assert_eq!(
+            columns[2].constraints,
+            vec![Constraint::References { table: TableName { schema: None, name: "users".to_string() }, column: None, on_delete: None, on_update: None }]
+        );
+    }
+
+    #[test]
+    fn table_constraints() {
+        let (_, constraints) = create_table(
+            "CREATE TABLE t (a INT, b INT, UNIQUE (a, b), CHECK (a > 0), FOREIGN KEY (a, b) REFERENCES other (x, y) ON DELETE RESTRICT);",
+        );
+        assert_eq!(
+            constraints,
+            vec![
+                TableConstraint::Unique(vec!["a".to_string(), "b".to_string()]),
+                TableConstraint::Check(a_greater_than_zero()),
+                TableConstraint::ForeignKey {
+                    columns: vec!["a".to_string(), "b".to_string()],
+                    table: TableName { schema: None, name: "other".to_string() },
+                    referenced_columns: vec!["x".to_string(), "y".to_string()],
+                    on_delete: Some(RefAction::Restrict),
+                    on_update: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn referenced_tables_can_have_a_schema() {
+        let (columns, constraints) =
+            create_table("CREATE TABLE t (a INT REFERENCES s.users(id), b INT, FOREIGN KEY (b) REFERENCES s.other (x));");
+        assert_eq!(
+            columns[0].constraints,
+            vec![Constraint::References {
+                table: TableName { schema: Some("s".to_string()), name: "users".to_string() },
+                column: Some("id".to_string()),
+                on_delete: None,
+                on_update: None,
+            }]
+        );
+        assert_eq!(
+            constraints,
+            vec![TableConstraint::ForeignKey {
+                columns: vec!["b".to_string()],
+                table: TableName { schema: Some("s".to_string()), name: "other".to_string() },
+                referenced_columns: vec!["x".to_string()],
+                on_delete: None,
+                on_update: None,
+            }]
+        );
+        let statement = parse("CREATE TABLE t (a INT REFERENCES s.users(id));").unwrap();
+        assert_eq!(statement.referenced_tables(), vec!["t".to_string(), "s.users".to_string()]);
+    }
+
+    #[test]
+    fn date_and_time_columns_take_constraints() {
+        let (columns, _) = create_table("CREATE TABLE t (created_at TIMESTAMP NOT NULL, d DATE UNIQUE, tm TIME NOT NULL PRIMARY KEY);");
+        let types_and_constraints: Vec<(&DBType, &Vec<Constraint>)> = columns.iter().map(|c| (&c.column_type, &c.constraints)).collect();
+        assert_eq!(
+            types_and_constraints,
+            [
+                (&DBType::Timestamp, &vec![Constraint::NotNull]),
+                (&DBType::Date, &vec![Constraint::Unique]),
+                (&DBType::Time, &vec![Constraint::NotNull, Constraint::PrimaryKey]),
+            ]
+        );
+    }
+
+    fn returning_of(sql: &str) -> Vec<Expression> {
+        match parse(sql).unwrap() {
+            Statement::Insert { returning, .. } | Statement::Update { returning, .. } | Statement::Delete { returning, .. } => returning,
+            other => panic!("expected INSERT, UPDATE or DELETE, got {:?}", other),
+        }
+    }
+
+    fn values_of(sql: &str) -> Result<Vec<Vec<Expression>>, ParseError> {
+        match parse(sql)? {
+            Statement::Insert { values, .. } => Ok(values),
+            other => panic!("expected INSERT, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn default_can_be_written_in_place_of_a_value() {
+        assert_eq!(
+            values_of("INSERT INTO t (a, b) VALUES (1, DEFAULT), (DEFAULT, 2);"),
+            Ok(vec![vec![Expression::Number(1), Expression::Default], vec![Expression::Default, Expression::Number(2)]])
+        );
+        // DEFAULT is a whole value, it can't be part of an expression
+        assert!(matches!(
+            parse("INSERT INTO t VALUES (DEFAULT + 1);"),
+            Err(ParseError::UnexpectedToken { found: Token::Plus, .. })
+        ));
+        // Only the columns are names, DEFAULT isn't one
+        assert_eq!(parse("INSERT INTO t (a) VALUES (DEFAULT);").unwrap().referenced_columns(), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn every_row_of_values_has_the_same_length() {
+        let mismatch = |row, expected, found| Err(ParseError::ValueCountMismatch { row, expected, found });
+        // DEFAULT counts as a value
+        assert!(values_of("INSERT INTO t (a, b) VALUES (1, DEFAULT), (DEFAULT, 2);").is_ok());
+        assert_eq!(values_of("INSERT INTO t (a, b) VALUES (1, 2), (DEFAULT);"), mismatch(1, 2, 1));
+        assert_eq!(values_of("INSERT INTO t (a, b) VALUES (1, 2, DEFAULT);"), mismatch(0, 2, 3));
+        // Without the columns, the first row decides
+        assert!(values_of("INSERT INTO t VALUES (1, 2, 3), (4, 5, DEFAULT);").is_ok());
+        assert_eq!(values_of("INSERT INTO t VALUES (1, 2, 3), (4, 5, 6), (7, 8);"), mismatch(2, 3, 2));
+        assert_eq!(
+            ParseError::ValueCountMismatch { row: 1, expected: 2, found: 1 }.to_string(),
+            "Row at index 1 of VALUES has 1 value(s), expected 2"
+        );
+    }
+
+    #[test]
+    fn insert_with_returning() {
+        assert_eq!(
+            parse("INSERT INTO users (id, name) VALUES (1, 'a'), (2, 'b') RETURNING id, name;"),
+            Ok(Statement::Insert {
+                table_name: TableName { schema: None, name: "users".to_string() },
+                columns: vec!["id".to_string(), "name".to_string()],
+                values: vec![
+                    vec![Expression::Number(1), Expression::String("a".to_string())],
+                    vec![Expression::Number(2), Expression::String("b".to_string())],
+                ],
+                returning: vec![Expression::Identifier("id".to_string()), Expression::Identifier("name".to_string())],
+            })
+        );
+    }
+
+    #[test]
+    fn update_with_returning() {
+        assert_eq!(
+            parse("UPDATE users SET name = 'x' WHERE id > 0 RETURNING *;"),
+            Ok(Statement::Update {
+                table_name: TableName { schema: None, name: "users".to_string() },
+                assignments: vec![Assignment { column: "name".to_string(), value: Expression::String("x".to_string()) }],
+                r#where: Some(Expression::BinaryOperation {
+                    left_operand: Box::new(Expression::Identifier("id".to_string())),
+                    operator: BinaryOperator::GreaterThan,
+                    right_operand: Box::new(Expression::Number(0)),
+                }),
+                returning: vec![Expression::AllColumns],
+            })
+        );
+    }
+
+    fn assignments_of(sql: &str) -> Vec<Assignment> {
+        match parse(sql).unwrap() {
+            Statement::Update { assignments, .. } => assignments,
+            other => panic!("expected UPDATE, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn set_values_can_use_the_columns_they_change() {
+        let column = |name: &str| Box::new(Expression::Identifier(name.to_string()));
+        // The value is a full expression, so it can read the old value of its own column
+        assert_eq!(
+            assignments_of("UPDATE t SET count = count + 1;"),
+            vec![Assignment {
+                column: "count".to_string(),
+                value: Expression::BinaryOperation { left_operand: column("count"), operator: BinaryOperator::Plus, right_operand: Box::new(Expression::Number(1)) },
+            }]
+        );
+        // ... or of the other columns, like swapping two of them
+        assert_eq!(
+            assignments_of("UPDATE t SET a = b, b = a WHERE a <> b;"),
+            vec![
+                Assignment { column: "a".to_string(), value: Expression::Identifier("b".to_string()) },
+                Assignment { column: "b".to_string(), value: Expression::Identifier("a".to_string()) },
+            ]
+        );
+        assert_eq!(
+            assignments_of("UPDATE t SET price = price * 2 - discount;"),
+            vec![Assignment {
+                column: "price".to_string(),
+                value: Expression::BinaryOperation {
+                    left_operand: Box::new(Expression::BinaryOperation {
+                        left_operand: column("price"),
+                        operator: BinaryOperator::Multiply,
+                        right_operand: Box::new(Expression::Number(2)),
+                    }),
+                    operator: BinaryOperator::Minus,
+                    right_operand: column("discount"),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn returning_is_read_like_a_select_list() {
+        assert_eq!(returning_of("DELETE FROM users;"), vec![]);
+        assert_eq!(returning_of("DELETE FROM users RETURNING *;"), vec![Expression::AllColumns]);
+        assert_eq!(
+            returning_of("DELETE FROM users WHERE id = 1 RETURNING users.*, id"),
+            vec![Expression::QualifiedAllColumns("users".to_string()), Expression::Identifier("id".to_string())]
+        );
+        assert!(matches!(parse("DELETE FROM users RETURNING a,;"), Err(ParseError::TrailingComma { before: Token::Semicolon, .. })));
+        assert!(matches!(parse("DELETE FROM users RETURNING;"), Err(ParseError::UnexpectedToken { found: Token::Semicolon, .. })));
+        // RETURNING is the last clause
+        assert!(parse("UPDATE t SET a = 1 RETURNING a WHERE b = 1;").is_err());
+    }
+
+    #[test]
+    fn returning_is_not_mysql() {
+        let mysql = ParserOptions { dialect: Dialect::MySql, ..ParserOptions::default() };
+        let parse_mysql = |sql: &str| Parser::with_options(tokenize(sql).unwrap(), mysql.clone()).parse();
+        assert!(parse_mysql("DELETE FROM t WHERE a = 1;").is_ok());
+        assert!(matches!(parse_mysql("DELETE FROM t RETURNING a;"), Err(ParseError::UnexpectedToken { found: Token::Keyword(_), .. })));
+    }
+
+    #[test]
+    fn null_is_a_value_in_comparisons() {
+        match parse("SELECT a FROM t WHERE x = NULL;").unwrap() {
+            Statement::Select { r#where, .. } => assert_eq!(
+                r#where,
+                Some(Expression::BinaryOperation {
+                    left_operand: Box::new(Expression::Identifier("x".to_string())),
+                    operator: BinaryOperator::Equal,
+                    right_operand: Box::new(Expression::Null),
+                })
+            ),
+            other => panic!("expected SELECT, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn default_values_of_columns() {
+        let (columns, _) = create_table("CREATE TABLE t (a INT DEFAULT NULL, b INT DEFAULT 0 NOT NULL, c TEXT NOT NULL DEFAULT 'x');");
+        assert_eq!(columns[0].constraints, vec![Constraint::Default(Expression::Null)]);
+        assert_eq!(columns[1].constraints, vec![Constraint::Default(Expression::Number(0)), Constraint::NotNull]);
+        assert_eq!(columns[2].constraints, vec![Constraint::NotNull, Constraint::Default(Expression::String("x".to_string()))]);
+        assert_eq!(
+            parse("CREATE TABLE t (a INT DEFAULT NULL);").unwrap().to_string(),
+            "CREATE TABLE t (a INT DEFAULT NULL);"
+        );
+    }
+
+    fn select_columns(sql: &str) -> Vec<Expression> {
+        match parse(sql).unwrap() {
+            Statement::Select { columns, .. } => columns,
+            other => panic!("expected SELECT, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn star_is_all_columns_or_multiplication() {
+        let column = |name: &str| Expression::Identifier(name.to_string());
+        let multiply = |left: Expression, right: Expression| Expression::BinaryOperation {
+            left_operand: Box::new(left),
+            operator: BinaryOperator::Multiply,
+            right_operand: Box::new(right),
+        };
+        let count_all = || Expression::FunctionCall { name: "COUNT".to_string(), args: vec![Expression::AllColumns], distinct: false, window: None };
+
+        assert_eq!(select_columns("SELECT a*b FROM t;"), vec![multiply(column("a"), column("b"))]);
+        assert_eq!(select_columns("SELECT * FROM t;"), vec![Expression::AllColumns]);
+        assert_eq!(select_columns("SELECT COUNT(*) FROM t;"), vec![count_all()]);
+        assert_eq!(select_columns("SELECT COUNT(*) * 2 FROM t;"), vec![multiply(count_all(), Expression::Number(2))]);
+    }
+}
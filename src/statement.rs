@@ -1,16 +1,60 @@
 use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use crate::pratt_parsing::{associativity, get_precedence, Associativity, IN_PRECEDENCE, IS_PRECEDENCE, LIKE_PRECEDENCE, NOT_PRECEDENCE};
+use crate::token::Placeholder;
+use crate::tokenizer::keyword_for;
+use crate::visitor::{walk_expression, walk_statement, Visitor};
 
-/// The main entity of the whole parser. `Statement` is implemented as an enumeration because adding functionality is as easy as adding an enumeration constant and implementing functionality for that enumeration constant (implementation in the database command interpreter, which is not a part of this project). Parsing any correct `SELECT` or `CREATE`  (or `UPDATE`, `INSERT INTO`, ... hypothetically) statement should be turned into an instance of this enumeration. Ultimately, your main parser function (something like `build_statement(query: &str) -> Statement`) should return this enumeration.
+/// The main entity of the whole parser. `Statement` is implemented as an enumeration because adding functionality is as easy as adding an enumeration constant and implementing functionality for that enumeration constant (implementation in the database command interpreter, which is not a part of this project). Parsing any correct `SELECT` or `CREATE`  (or `UPDATE`, `INSERT INTO`, ...) statement should be turned into an instance of this enumeration. Ultimately, your main parser function (something like `build_statement(query: &str) -> Statement`) should return this enumeration.
 ///
-/// The `SELECT` statement has four components:
+/// The `SELECT` statement has eight components:
 /// 1. `columns` – A vector of columns from the selected table that the database should return.
-/// 2. `from` – A simple string, containing a table that is being queried (we aren't doing joins because they complicate stuff too much for this project).
+/// 2. `from` – The `TableName` of the table that is being queried, which can have a schema (`public.users`) (we aren't doing joins because they complicate stuff too much for this project).
 /// 3. `where` – A single expression that is the actual filter for the database query. It is wrapped in an `Option` because not every `SELECT` query contains a filter. The actual name is `r#where` because in Rust, `where` is a reserved keyword, and the prefix `r#` means: interpret this token as a raw string, do not check for keyword matches.
-/// 4. `orderby` – A vector of expressions that define how should the data be ordered. A vector is needed because the data can be ordered by the first column, and then all data that has the same first column can be ordered by the second column, ... Also, the data can be ordered not simply by columns, but by complex expressions as well.
+/// 4. `orderby` – A vector of `OrderByItem`s (an expression with an optional direction and `NULLS FIRST`/`NULLS LAST`) that define how should the data be ordered. A vector is needed because the data can be ordered by the first column, and then all data that has the same first column can be ordered by the second column, ... Also, the data can be ordered not simply by columns, but by complex expressions as well.
+/// 5. `ctes` – The common table expressions from a `WITH` clause in front of the `SELECT` (`WITH recent AS (SELECT ...) SELECT * FROM recent;`), empty when there is no `WITH`. Each of them is a named query that can be used like a table in the main query.
+/// 6. `limit` – How many rows are returned and how many are skipped before them (`LIMIT 10 OFFSET 20`), `None` when there is no `LIMIT`.
+/// 7. `group_by` – The expressions of the `GROUP BY` clause, rows with the same values of them are combined into one. Empty when there is no `GROUP BY`.
+/// 8. `with_rollup` – Set when the `GROUP BY` list is followed by `WITH ROLLUP` (MySQL), which also returns subtotal rows for the groups.
 ///
-/// The `CREATE TABLE` statement has two components:
-/// 1. `table_name` – A simple string, the name of the table.
+/// The `CREATE TABLE` statement has four components:
+/// 1. `table_name` – The `TableName` of the table, with or without a schema (`app.events`).
 /// 2. `column_list` – A vector of table column types, where each table column contains the definition of one column.
+/// 3. `table_constraints` – A vector of constraints that are written as separate items of the list instead of after a column, like `PRIMARY KEY (a, b)`. They can span several columns.
+/// 4. `if_not_exists` – Set when `IF NOT EXISTS` is written after `CREATE TABLE`, then the statement does nothing if the table already exists.
+///
+/// The `ALTER TABLE` statement has two components:
+/// 1. `table_name` – The `TableName` of the table that is changed.
+/// 2. `action` – What should be done with the table, defined in the `AlterTableAction` enum.
+///
+/// The `CREATE TABLE ... AS SELECT ...` statement (`CreateTableAs`) creates a table from the result of a query, so it has no column list:
+/// 1. `table_name` – The `TableName` of the new table.
+/// 2. `if_not_exists` – The same as in `CREATE TABLE`.
+/// 3. `query` – The `SELECT` statement whose result fills the table. It is boxed because a `Statement` can't directly contain another `Statement`.
+///
+/// The `TRUNCATE TABLE` statement (`Truncate`) removes all rows of a table, it only has the `table_name`.
+///
+/// The `CREATE INDEX` statement (`CreateIndex`) has four components:
+/// 1. `name` – The name of the new index.
+/// 2. `table` – The `TableName` of the table the index is on (after `ON`).
+/// 3. `columns` – The indexed columns in parentheses. They are `OrderByItem`s, because every column can have its own direction (`b DESC`).
+/// 4. `unique` – Set for `CREATE UNIQUE INDEX`, then two rows can't have the same values in the indexed columns.
+///
+/// `EXPLAIN` in front of a statement (`Explain`) asks for the plan of the statement instead of running it:
+/// 1. `analyze` – Set for `EXPLAIN ANALYZE`, which runs the statement too and reports how long each step took.
+/// 2. `inner` – The statement being explained.
+///
+/// Two queries combined with `UNION`, `EXCEPT` or `INTERSECT` (`SetOperation`) have four components:
+/// 1. `op` – Which set operator it is, see `SetOp`. `MINUS` is parsed as `SetOp::Except`.
+/// 2. `all` – Set for `UNION ALL` (and the others with `ALL`), which keeps duplicate rows.
+/// 3. `left` and `right` – The two queries. Longer chains are nested: `a UNION b UNION c` has `a UNION b` on the left, and `a UNION b INTERSECT c` has `b INTERSECT c` on the right, because `INTERSECT` binds tighter.
+///    An `ORDER BY` or `LIMIT` at the end is parsed as part of the last `SELECT`.
+///
+/// The statements that change rows, `INSERT INTO` (`Insert`), `UPDATE` (`Update`) and `DELETE FROM` (`Delete`), all have a `table_name` and a `returning` list:
+/// the columns after `RETURNING` (PostgreSQL), which the statement gives back for every row it changed. They are read like the columns of a `SELECT`, so `*` is `Expression::AllColumns`. The list is empty when there is no `RETURNING`.
+/// 1. `Insert` also has `columns`, the names in parentheses after the table (empty when they aren't written, then the values go to all columns in order), and `values`, one vector of expressions for every row after `VALUES`. A value can be `DEFAULT` (`Expression::Default`). Every row has as many values as there are `columns` (or as the first row, when the columns aren't written).
+/// 2. `Update` has `assignments`, the `column = value` pairs after `SET` (see `Assignment`), and an optional `where`, the same as in `SELECT`.
+/// 3. `Delete` has only an optional `where`, without it every row of the table is deleted.
 ///
 /// Examples:
 ///
@@ -19,15 +63,19 @@ use std::fmt::{Debug, Display, Formatter};
 /// SELECT name, surname FROM users;
 /// ```
 /// is a `SELECT` statement that,  when parsed, looks like this:
-/// ```rust
+/// ```rust,ignore
 /// Statement::Select {
 ///     columns: vec![
-/// 		Expression::Identifier("name".to_string()),
-/// 		Expression:Identifier("surname".to_string())
-/// 	],
-///     from: "users".to_string(),
+///         Expression::Identifier("name".to_string()),
+///         Expression:Identifier("surname".to_string())
+///     ],
+///     from: TableName { schema: None, name: "users".to_string() },
 ///     r#where: None,
-///     orderby: vec![]
+///     orderby: vec![],
+///     ctes: vec![],
+///     limit: None,
+///     group_by: vec![],
+///     with_rollup: false
 /// }
 /// ```
 /// ---
@@ -35,7 +83,7 @@ use std::fmt::{Debug, Display, Formatter};
 /// SELECT age * 5, 'this is a string' FROM users;
 /// ```
 /// is a `SELECT` statement that,  when parsed, looks like this:
-/// ```rust
+/// ```rust,ignore
 /// Statement::Select {
 ///     columns: vec![
 ///         Expression::BinaryOperation {
@@ -45,23 +93,27 @@ use std::fmt::{Debug, Display, Formatter};
 ///         },
 ///         Expression::String("this is a string".to_string()),
 ///     ],
-///     from: "users".to_string(),
+///     from: TableName { schema: None, name: "users".to_string() },
 ///     r#where: None,
-///     orderby: vec![]
+///     orderby: vec![],
+///     ctes: vec![],
+///     limit: None,
+///     group_by: vec![],
+///     with_rollup: false
 /// }
 /// ```
 /// ---
 /// ```sql
-/// SELECT name, surname FROM users WHERE name = \"Voldemort\" AND surname = 'Riddle';
+/// SELECT name, surname FROM users WHERE name = 'Voldemort' AND surname = 'Riddle';
 /// ```
 /// is a  `SELECT` statement that, when parsed, looks like this:
-/// ```rust
+/// ```rust,ignore
 /// Statement::Select {
 ///     columns: [
 ///         Expression::Identifier("name".to_string()),
 ///         Expression::Identifier("surname".to_string()),
 ///     ],
-///     from: "users".to_string(),
+///     from: TableName { schema: None, name: "users".to_string() },
 ///     r#where: Some(
 ///         Expression::BinaryOperation {
 ///             left_operand: Box::new(Expression::BinaryOperation {
@@ -77,7 +129,11 @@ use std::fmt::{Debug, Display, Formatter};
 ///             }),
 ///         },
 ///     ),
-///     orderby: vec![]
+///     orderby: vec![],
+///     ctes: vec![],
+///     limit: None,
+///     group_by: vec![],
+///     with_rollup: false
 /// }
 /// ```
 ///  ---
@@ -85,17 +141,17 @@ use std::fmt::{Debug, Display, Formatter};
 /// SELECT id, salary FROM users ORDER BY salary - 2 * 10 ASC, id DESC;
 /// ```
 /// is a  `SELECT` statement that, when parsed, looks like this:
-/// ```rust
+/// ```rust,ignore
 /// Statement::Select {
 ///     columns: vec![
 ///         Expression::Identifier("id".to_string()),
 ///         Expression::Identifier("salary".to_string()),
 ///     ],
-///     from: "users".to_string(),
+///     from: TableName { schema: None, name: "users".to_string() },
 ///     r#where: None,
 ///     orderby: vec![
-///         Expression::UnaryOperation {
-///             operand: Box::new(Expression::BinaryOperation {
+///         OrderByItem {
+///             expr: Expression::BinaryOperation {
 ///                 left_operand: Box::new(Expression::Identifier("salary".to_string())),
 ///                 operator: BinaryOperator::Minus,
 ///                 right_operand: Box::new(Expression::BinaryOperation {
@@ -103,14 +159,20 @@ use std::fmt::{Debug, Display, Formatter};
 ///                     operator: BinaryOperator::Multiply,
 ///                     right_operand: Box::new(Expression::Number(10)),
 ///                 }),
-///             }),
-///             operator: UnaryOperator::Asc,
+///             },
+///             direction: Some(OrderDirection::Asc),
+///             nulls: None,
 ///         },
-///         Expression::UnaryOperation {
-///             operand: Box::new(Expression::Identifier("id".to_string())),
-///             operator: UnaryOperator::Desc,
+///         OrderByItem {
+///             expr: Expression::Identifier("id".to_string()),
+///             direction: Some(OrderDirection::Desc),
+///             nulls: None,
 ///         },
 ///     ],
+///     ctes: vec![],
+///     limit: None,
+///     group_by: vec![],
+///     with_rollup: false
 /// }
 /// ```
 ///  ---
@@ -118,12 +180,12 @@ use std::fmt::{Debug, Display, Formatter};
 /// SELECT id FROM registered_users WHERE password_encryption = TRUE ORDER BY id DESC;
 /// ```
 /// is a  `SELECT` statement that, when parsed, looks like this:
-/// ```rust
+/// ```rust,ignore
 /// Statement::Select {
 ///     columns: vec![
 ///         Expression::Identifier("id".to_string())
 ///     ],
-///     from: "registered_users".to_string(),
+///     from: TableName { schema: None, name: "registered_users".to_string() },
 ///     r#where: Some(
 ///         Expression::BinaryOperation {
 ///             left_operand: Box::new(Expression::Identifier("password_encryption".to_string())),
@@ -132,25 +194,30 @@ use std::fmt::{Debug, Display, Formatter};
 ///         }
 ///     ),
 ///     orderby: vec![
-///         Expression::UnaryOperation {
-///             operand: Box::new(Expression::Identifier("id".to_string())),
-///             operator: UnaryOperator::Desc
+///         OrderByItem {
+///             expr: Expression::Identifier("id".to_string()),
+///             direction: Some(OrderDirection::Desc),
+///             nulls: None
 ///         }
-///     ]
+///     ],
+///     ctes: vec![],
+///     limit: None,
+///     group_by: vec![],
+///     with_rollup: false
 /// }
 /// ```
 /// ---
 /// ```sql
 /// CREATE TABLE simple_table(
-/// 	int_col INT,
-/// 	string_col VARCHAR(255),
-/// 	bool_col BOOL
+///     int_col INT,
+///     string_col VARCHAR(255),
+///     bool_col BOOL
 /// );
 /// ```
 /// is a  `CREATE TABLE` statement that, when parsed, looks like this:
-/// ```rust
+/// ```rust,ignore
 /// Statement::CreateTable {
-///     table_name: "simple_table".to_string(),
+///     table_name: TableName { schema: None, name: "simple_table".to_string() },
 ///     column_list: vec![
 ///         TableColumn {
 ///             column_name: "int_col".to_string(),
@@ -167,22 +234,24 @@ use std::fmt::{Debug, Display, Formatter};
 ///             column_type: DBType::Bool,
 ///             constraints: vec![],
 ///         },
-///     ]
+///     ],
+///     table_constraints: vec![],
+///     if_not_exists: false
 /// }
 /// ```
 /// ---
 /// ```sql
 /// CREATE TABLE complex_table(
-/// 	id INT PRIMARY KEY,
-/// 	email VARCHAR(255) NOT NULL,
-/// 	is_junior BOOL,
-/// 	age INT CHECK(age >= 18) CHECK(age <= 65)
+///     id INT PRIMARY KEY,
+///     email VARCHAR(255) NOT NULL,
+///     is_junior BOOL,
+///     age INT CHECK(age >= 18) CHECK(age <= 65)
 /// );
 /// ```
 /// is a  `CREATE TABLE` statement that, when parsed, looks like this:
-/// ```rust
+/// ```rust,ignore
 /// Statement::CreateTable {
-///     table_name: Expression::Identifier("complex_table".to_string()),
+///     table_name: TableName { schema: None, name: "complex_table".to_string() },
 ///     column_list: vec![
 ///         TableColumn {
 ///             column_name: Expression::Identifier("id".to_string()),
@@ -220,6 +289,8 @@ use std::fmt::{Debug, Display, Formatter};
 ///             ],
 ///         },
 ///     ],
+///     table_constraints: vec![],
+///     if_not_exists: false
 /// }
 /// ```
 /// ---
@@ -232,18 +303,404 @@ use std::fmt::{Debug, Display, Formatter};
 /// ```sql
 /// CREATE TABLE work_hours(num_hours INT)
 /// ```
-/// is a string, that, the parser should throw an error to the user when it encounters it (no semicolon at the end).
-#[derive(Debug, PartialEq)]
+/// has no semicolon at the end. It is an error when `ParserOptions::require_semicolon` is set (the default of `Parser::new`, and what the interactive program uses), while the library functions like `parse` turn it off and accept it as the last statement of the input.
+// A SELECT has many more parts than the other statements, so its variant is much bigger than theirs.
+// Statements are parsed one at a time and not kept in large numbers, so the extra size doesn't matter
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub enum Statement {
     Select {
         columns: Vec<Expression>,
-        from: String,
+        from: TableName,
         r#where: Option<Expression>,
-        orderby: Vec<Expression>,
+        orderby: Vec<OrderByItem>,
+        ctes: Vec<CommonTableExpression>,
+        limit: Option<Limit>,
+        group_by: Vec<Expression>,
+        with_rollup: bool,
     },
     CreateTable {
-        table_name: String,
+        table_name: TableName,
         column_list: Vec<TableColumn>,
+        table_constraints: Vec<TableConstraint>,
+        if_not_exists: bool,
+    },
+    AlterTable {
+        table_name: TableName,
+        action: AlterTableAction,
+    },
+    CreateTableAs {
+        table_name: TableName,
+        if_not_exists: bool,
+        query: Box<Statement>,
+    },
+    Truncate {
+        table_name: TableName,
+    },
+    SetOperation {
+        op: SetOp,
+        all: bool,
+        left: Box<Statement>,
+        right: Box<Statement>,
+    },
+    CreateIndex {
+        name: String,
+        table: TableName,
+        columns: Vec<OrderByItem>,
+        unique: bool,
+    },
+    Explain {
+        analyze: bool,
+        inner: Box<Statement>,
+    },
+    Insert {
+        table_name: TableName,
+        columns: Vec<String>,
+        values: Vec<Vec<Expression>>,
+        returning: Vec<Expression>,
+    },
+    Update {
+        table_name: TableName,
+        assignments: Vec<Assignment>,
+        r#where: Option<Expression>,
+        returning: Vec<Expression>,
+    },
+    Delete {
+        table_name: TableName,
+        r#where: Option<Expression>,
+        returning: Vec<Expression>,
+    },
+}
+
+/// Only the kind of a `Statement`, one constant for each of its variants (see `Statement::kind`). Handy for logging or counting statements, when the contents don't matter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatementKind {
+    Select,
+    CreateTable,
+    AlterTable,
+    CreateTableAs,
+    Truncate,
+    SetOperation,
+    CreateIndex,
+    Explain,
+    Insert,
+    Update,
+    Delete,
+}
+
+/// The operator of a `SetOperation`: `Union` returns the rows of both queries, `Except` the rows of the left one that aren't in the right one, and `Intersect` the rows that are in both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SetOp {
+    Union,
+    Except,
+    Intersect,
+}
+
+impl SetOp {
+    // INTERSECT binds tighter than UNION and EXCEPT, like AND binds tighter than OR
+    pub fn precedence(&self) -> u8 {
+        match self {
+            SetOp::Union | SetOp::Except => 1,
+            SetOp::Intersect => 2,
+        }
+    }
+}
+
+/// The change made by an `ALTER TABLE` statement. `AddColumn` contains a full column definition, the same as in `CREATE TABLE` (so it can have constraints), while `DropColumn` only needs the name of the column that is removed and, if it is written, what happens to the things that depend on the column (`drop_behavior`).
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum AlterTableAction {
+    AddColumn(TableColumn),
+    DropColumn { name: String, drop_behavior: Option<DropBehavior> },
+}
+
+/// What `DROP COLUMN` does with the objects that use the column (like views or constraints): `Cascade` drops them too, `Restrict` refuses to drop the column.
+/// When it isn't written, the database uses its default (usually `RESTRICT`).
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum DropBehavior {
+    Cascade,
+    Restrict,
+}
+
+/// One `column = value` item of the `SET` list of an `UPDATE`, like `price = price * 2`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Assignment {
+    pub column: String,
+    pub value: Expression,
+}
+
+/// One named query of a `WITH` clause, like `recent (id, total) AS (SELECT id, total FROM orders)`.
+/// 1. `name` – The name the main query uses to refer to this query, like a table name.
+/// 2. `columns` – Names for the columns of the query's result, written in parentheses after the name. Empty when they aren't written, then the columns keep their own names.
+/// 3. `query` – The `SELECT` in parentheses after `AS`. It is boxed because a `Statement` can't directly contain another `Statement`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct CommonTableExpression {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub query: Box<Statement>,
+}
+
+/// One item of an `ORDER BY` list, like `salary DESC NULLS LAST`.
+/// 1. `expr` – The expression the rows are sorted by.
+/// 2. `direction` – `ASC` or `DESC`, `None` when it isn't written (the database then sorts ascending).
+/// 3. `nulls` – Where `NULL` values go (`NULLS FIRST` or `NULLS LAST`), `None` when it isn't written (then it depends on the database).
+///
+/// A number as the expression (`ORDER BY 2`) is a position in the `SELECT` list, see `ordinal`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct OrderByItem {
+    pub expr: Expression,
+    pub direction: Option<OrderDirection>,
+    pub nulls: Option<NullsOrder>,
+}
+
+impl OrderByItem {
+    // The position in the SELECT list this item sorts by (ORDER BY 2 is the second column, counted from 1),
+    // None when it sorts by an expression. The parser checks that the position is one of the columns
+    pub fn ordinal(&self) -> Option<usize> {
+        match self.expr {
+            Expression::Number(n) => usize::try_from(n).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// The value of a float literal (`Expression::Float`). It is a separate type so that expressions can be compared with `Eq` and used as keys of a `HashMap`, which a plain `f64` doesn't allow: two literals are equal when their bits are equal, so `NaN` is equal to itself and `0.0` is not equal to `-0.0`.
+#[derive(Debug, Clone, Copy)]
+pub struct FloatLiteral(pub f64);
+
+impl PartialEq for FloatLiteral {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for FloatLiteral {}
+
+// Hashes the same bits that eq compares, so equal literals always have the same hash
+impl Hash for FloatLiteral {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+/// The window of a window function, what is written in the parentheses after `OVER`. Both lists are empty for `OVER ()`, a window of all rows.
+/// 1. `partition_by` – The expressions of `PARTITION BY`, the function is computed separately for the rows with the same values of them.
+/// 2. `order_by` – The `ORDER BY` items inside the window, in which order the rows of one partition are gone through.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct WindowSpec {
+    pub partition_by: Vec<Expression>,
+    pub order_by: Vec<OrderByItem>,
+}
+
+/// The name of a table, as it is written after `FROM`, `CREATE TABLE`, `ALTER TABLE` and `TRUNCATE`.
+/// 1. `schema` – The schema the table is in, like `public` in `public.users`. `None` when only the table name is written.
+/// 2. `name` – The name of the table itself.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct TableName {
+    pub schema: Option<String>,
+    pub name: String,
+}
+
+impl TableName {
+    // The name as plain text (schema.name), without the quotes that Display adds to names that need them
+    pub fn qualified_name(&self) -> String {
+        match &self.schema {
+            Some(schema) => format!("{}.{}", schema, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// The `LIMIT` clause of a `SELECT`, like `LIMIT 10 OFFSET 20`.
+/// 1. `count` – The most rows that are returned.
+/// 2. `offset` – How many rows are skipped first, `None` when there is no `OFFSET`. The MySQL form `LIMIT 20, 10` is stored the same way (the offset is written first there).
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Limit {
+    pub count: Expression,
+    pub offset: Option<Expression>,
+}
+
+/// The sorting direction of an `ORDER BY` item.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum OrderDirection {
+    Asc,
+    Desc,
+}
+
+/// Where `NULL` values are placed when sorting, written as `NULLS FIRST` or `NULLS LAST` after the direction.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+/// The word between a comparison and its subquery in a quantified comparison like `x = ANY (SELECT ...)`. `Some` means the same as `Any`, it is only kept so the statement is written back the way it was.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum Quantifier {
+    All,
+    Any,
+    Some,
+}
+
+/// The unit of an `INTERVAL` literal.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum IntervalUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+impl Statement {
+    // Which kind of statement this is, without any of its contents
+    pub fn kind(&self) -> StatementKind {
+        match self {
+            Statement::Select { .. } => StatementKind::Select,
+            Statement::CreateTable { .. } => StatementKind::CreateTable,
+            Statement::AlterTable { .. } => StatementKind::AlterTable,
+            Statement::CreateTableAs { .. } => StatementKind::CreateTableAs,
+            Statement::Truncate { .. } => StatementKind::Truncate,
+            Statement::SetOperation { .. } => StatementKind::SetOperation,
+            Statement::CreateIndex { .. } => StatementKind::CreateIndex,
+            Statement::Explain { .. } => StatementKind::Explain,
+            Statement::Insert { .. } => StatementKind::Insert,
+            Statement::Update { .. } => StatementKind::Update,
+            Statement::Delete { .. } => StatementKind::Delete,
+        }
+    }
+
+    // Every table this statement uses (the FROM table, the created or changed table, tables in REFERENCES, ...),
+    // each name only once, in the order they first appear. Useful for finding which tables a migration depends on
+    pub fn referenced_tables(&self) -> Vec<String> {
+        let mut names = NameCollector::default();
+        names.visit_statement(self);
+        names.tables
+    }
+
+    // Every column this statement uses, anywhere: in the SELECT list, WHERE, ORDER BY, column definitions and constraints.
+    // Like referenced_tables, each name is returned only once
+    pub fn referenced_columns(&self) -> Vec<String> {
+        let mut names = NameCollector::default();
+        names.visit_statement(self);
+        names.columns
+    }
+
+    // Every placeholder of a prepared statement, in the order they are written, so a client knows how many values to bind.
+    // A placeholder used twice (like :id or $1 in two places) takes one value, so it is returned only once.
+    // The ? placeholders are numbered by the tokenizer in the order they appear, so each of them is a separate one
+    pub fn placeholders(&self) -> Vec<Placeholder> {
+        let mut collector = PlaceholderCollector::default();
+        collector.visit_statement(self);
+        collector.placeholders
+    }
+}
+
+#[derive(Default)]
+struct PlaceholderCollector {
+    placeholders: Vec<Placeholder>,
+}
+
+impl Visitor for PlaceholderCollector {
+    fn visit_expression(&mut self, expr: &Expression) {
+        if let Expression::Placeholder(placeholder) = expr {
+            if !self.placeholders.contains(placeholder) {
+                self.placeholders.push(placeholder.clone());
+            }
+        }
+        walk_expression(self, expr);
+    }
+}
+
+// Collects the distinct table and column names for referenced_tables and referenced_columns
+#[derive(Default)]
+struct NameCollector {
+    tables: Vec<String>,
+    columns: Vec<String>,
+}
+
+impl NameCollector {
+    fn add(names: &mut Vec<String>, name: &str) {
+        if !names.iter().any(|existing| existing == name) {
+            names.push(name.to_string());
+        }
+    }
+
+    fn add_column_definition(&mut self, column: &TableColumn) {
+        Self::add(&mut self.columns, &column.column_name);
+        for constraint in &column.constraints {
+            if let Constraint::References { table, column, .. } = constraint {
+                Self::add(&mut self.tables, &table.qualified_name());
+                if let Some(column) = column {
+                    Self::add(&mut self.columns, column);
+                }
+            }
+        }
+    }
+}
+
+impl Visitor for NameCollector {
+    // Names that aren't expressions (table names, column definitions, key lists) are taken here,
+    // the expressions are handled by visit_expression
+    fn visit_statement(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Select { from, .. } => Self::add(&mut self.tables, &from.qualified_name()),
+            Statement::CreateTable { table_name, column_list, table_constraints, .. } => {
+                Self::add(&mut self.tables, &table_name.qualified_name());
+                for column in column_list {
+                    self.add_column_definition(column);
+                }
+                for constraint in table_constraints {
+                    match constraint {
+                        TableConstraint::PrimaryKey(columns) | TableConstraint::Unique(columns) => {
+                            columns.iter().for_each(|column| Self::add(&mut self.columns, column));
+                        }
+                        TableConstraint::ForeignKey { columns, table, referenced_columns, .. } => {
+                            columns.iter().for_each(|column| Self::add(&mut self.columns, column));
+                            Self::add(&mut self.tables, &table.qualified_name());
+                            referenced_columns.iter().for_each(|column| Self::add(&mut self.columns, column));
+                        }
+                        TableConstraint::Check(_) => {}
+                    }
+                }
+            }
+            Statement::AlterTable { table_name, action } => {
+                Self::add(&mut self.tables, &table_name.qualified_name());
+                match action {
+                    AlterTableAction::AddColumn(column) => self.add_column_definition(column),
+                    AlterTableAction::DropColumn { name, .. } => Self::add(&mut self.columns, name),
+                }
+            }
+            Statement::CreateTableAs { table_name, .. } | Statement::Truncate { table_name } => Self::add(&mut self.tables, &table_name.qualified_name()),
+            // Both queries (or the explained statement) are statements of their own, walk_statement visits them
+            Statement::SetOperation { .. } | Statement::Explain { .. } => {}
+            // The indexed columns are expressions, they are taken by visit_expression
+            Statement::CreateIndex { table, .. } => Self::add(&mut self.tables, &table.qualified_name()),
+            Statement::Insert { table_name, columns, .. } => {
+                Self::add(&mut self.tables, &table_name.qualified_name());
+                columns.iter().for_each(|column| Self::add(&mut self.columns, column));
+            }
+            Statement::Update { table_name, assignments, .. } => {
+                Self::add(&mut self.tables, &table_name.qualified_name());
+                assignments.iter().for_each(|assignment| Self::add(&mut self.columns, &assignment.column));
+            }
+            Statement::Delete { table_name, .. } => Self::add(&mut self.tables, &table_name.qualified_name()),
+        }
+        walk_statement(self, stmt);
+    }
+
+    // A qualified name (users.id) uses both its table and its column
+    fn visit_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Identifier(name) => Self::add(&mut self.columns, name),
+            Expression::QualifiedIdentifier { table, column } => {
+                Self::add(&mut self.tables, table);
+                Self::add(&mut self.columns, column);
+            }
+            Expression::QualifiedAllColumns(table) => Self::add(&mut self.tables, table),
+            _ => {}
+        }
+        walk_expression(self, expr);
     }
 }
 
@@ -253,18 +710,20 @@ pub enum Statement {
 /// * complex - a number of other expressions (tree-like structure, unary and binary operations)
 /// * a single number
 /// * a single identifier (like a variable 'x')
-/// * a single string (when doing parsing of WHERE statements that do operations with strings, strings must be in matching quotes – either `""` or `''`)
+/// * a single string (when doing parsing of WHERE statements that do operations with strings, strings must be in single quotes `''`, because double quotes `""` are used for quoted identifiers like `"user name"`)
 /// * a boolean (only true or false)
+/// * the `NULL` literal
+/// * a tuple of expressions in parentheses, like `(a, b)` (a single expression in parentheses is just a group, not a tuple)
 ///
 /// Examples:
 ///
 /// ---
-/// ```
+/// ```text
 /// (13 + 7) - 4
 /// ```
 /// is an expression that contains two expressions:
 /// 1. `(13 + 7)` which is
-/// ```rust
+/// ```rust,ignore
 /// Expression::BinaryOperation {
 ///     left_operand: Box::new(Expression::Number(13)),
 ///     operator: BinaryOperator::Plus,
@@ -272,11 +731,11 @@ pub enum Statement {
 /// }
 /// ```
 /// 2. `4` which is
-/// ```rust
+/// ```rust,ignore
 /// Expression::Number(4)
 /// ```
 /// Therefore, the whole expression after parsing should look like this:
-/// ```rust
+/// ```rust,ignore
 /// Expression::BinaryOperation {
 ///     left_operand: Expression::BinaryOperation {
 ///         left_operand: Box::new(Expression::Number(13)),
@@ -288,12 +747,12 @@ pub enum Statement {
 /// }
 /// ```
 /// ---
-/// ```
-/// (5 - x) < (4 + y) OR name = "Donna"
+/// ```text
+/// (5 - x) < (4 + y) OR name = 'Donna'
 /// ```
 /// is an expression that contains five (three small and two combining) expressions:
 /// 1. `(5 - x)` which is
-/// ```rust
+/// ```rust,ignore
 /// Expression::BinaryOperation {
 ///     left_operand: Box::new(Expression::Number(5)),
 ///     operator: BinaryOperator::Minus,
@@ -301,15 +760,15 @@ pub enum Statement {
 /// }
 /// ```
 /// 2. `(4 - y)` which is
-/// ```rust
+/// ```rust,ignore
 /// Expression::BinaryOperation {
 ///     left_operand: Box::new(Expression::Number(4)),
 ///     operator: BinaryOperator::Plus,
 ///     right_operand: Box::new(Expression::Identifier("y".to_string()))
 /// }
 /// ```
-/// 3. `name = "Donna"` which is
-/// ```rust
+/// 3. `name = 'Donna'` which is
+/// ```rust,ignore
 /// Expression::BinaryOperation {
 ///     left_operand: Box::new(Expression::Identifier("name".to_string())),
 ///     operator: BinaryOperator::Equal,
@@ -317,7 +776,7 @@ pub enum Statement {
 /// }
 /// ```
 /// Therefore, the whole expression after parsing should look like this:
-/// ```rust
+/// ```rust,ignore
 /// Expression::BinaryOperation {
 ///     left_operand: Box::new(Expression::BinaryOperation {
 ///         left_operand: Box::new(Expression::BinaryOperation {
@@ -341,27 +800,163 @@ pub enum Statement {
 /// }
 /// ```
 /// ---
-/// ```
+/// ```text
 /// NOT some_boolean = TRUE
 /// ```
-/// should look like this:
-/// ```rust
-/// Expression::BinaryOperation {
-///     left_operand: Box::new(Expression::UnaryOperation {
+/// should look like this, because `NOT` binds looser than comparisons (but tighter than `AND` and `OR`):
+/// ```rust,ignore
+/// Expression::UnaryOperation {
+///     operand: Box::new(Expression::BinaryOperation {
 ///         left_operand: Box::new(Expression::Identifier("some_boolean".to_string())),
-///         operator: UnaryOperator::Not
+///         operator: BinaryOperator::Equal,
+///         right_operand: Box::new(Expression::Bool(true))
 ///     }),
-///     operator: BinaryOperator::Equal,
-///     right_operand: Box::new(Expression::Bool(true))
+///     operator: UnaryOperator::Not
 /// }
 /// ```
 /// ---
+/// ```text
+/// id = ? AND name = :name
+/// ```
+/// uses placeholders of a prepared statement. Their values are not known while parsing, so they stay as `Expression::Placeholder(Placeholder::Positional(1))` and `Expression::Placeholder(Placeholder::Named("name".to_string()))`.
+/// ---
+/// ```text
+/// users.id
+/// ```
+/// is a column name together with the table it belongs to: `Expression::QualifiedIdentifier { table: "users".to_string(), column: "id".to_string() }`. In the list of a `SELECT`, `users.*` (all columns of one table) is `Expression::QualifiedAllColumns("users".to_string())`.
+/// ---
+/// ```text
+/// COUNT(DISTINCT user_id)
+/// ```
+/// is a function call. `distinct` is only set when `DISTINCT` is written right after the opening parenthesis (a `*` argument like in `COUNT(*)` is `Expression::AllColumns`, and is only allowed as a whole argument, `*` anywhere else in an expression is multiplication):
+/// ```rust,ignore
+/// Expression::FunctionCall {
+///     name: "COUNT".to_string(),
+///     args: vec![Expression::Identifier("user_id".to_string())],
+///     distinct: true,
+///     window: None
+/// }
 /// ```
+/// ---
+/// ```text
+/// ROW_NUMBER() OVER (PARTITION BY dept ORDER BY salary DESC)
+/// ```
+/// is a window function: the function is computed over a window of rows (here the rows of the same `dept`, in the order of `salary`) instead of collapsing them into one. `window` is the `WindowSpec` after `OVER`:
+/// ```rust,ignore
+/// Expression::FunctionCall {
+///     name: "ROW_NUMBER".to_string(),
+///     args: vec![],
+///     distinct: false,
+///     window: Some(WindowSpec {
+///         partition_by: vec![Expression::Identifier("dept".to_string())],
+///         order_by: vec![OrderByItem {
+///             expr: Expression::Identifier("salary".to_string()),
+///             direction: Some(OrderDirection::Desc),
+///             nulls: None
+///         }]
+///     })
+/// }
+/// ```
+/// ---
+/// ```text
+/// NOT EXISTS (SELECT id FROM orders WHERE paid)
+/// ```
+/// is a subquery predicate. The query is a whole `SELECT` statement (without its semicolon), and `NOT EXISTS` is kept as one node with `negated` set instead of a `NOT` operation around it:
+/// ```rust,ignore
+/// Expression::Exists {
+///     query: Box::new(Statement::Select {
+///         columns: vec![Expression::Identifier("id".to_string())],
+///         from: TableName { schema: None, name: "orders".to_string() },
+///         r#where: Some(Expression::Identifier("paid".to_string())),
+///         orderby: vec![],
+///         ctes: vec![],
+///         limit: None,
+///         group_by: vec![],
+///         with_rollup: false
+///     }),
+///     negated: true
+/// }
+/// ```
+/// ---
+/// ```text
+/// status NOT IN ('deleted', 'banned')
+/// ```
+/// checks a value against a list of values. When the parentheses contain a `SELECT` instead, like `id IN (SELECT user_id FROM orders)`, it is `Expression::InSubquery` with the query in place of the list (a list can't mix values and a subquery):
+/// ```rust,ignore
+/// Expression::InList {
+///     expr: Box::new(Expression::Identifier("status".to_string())),
+///     list: vec![Expression::String("deleted".to_string()), Expression::String("banned".to_string())],
+///     negated: true
+/// }
+/// ```
+/// ---
+/// ```text
+/// CAST(price AS DECIMAL(10, 2))
+/// ```
+/// converts a value to another type. The type is parsed the same way as a column type in `CREATE TABLE`:
+/// ```rust,ignore
+/// Expression::Cast {
+///     expr: Box::new(Expression::Identifier("price".to_string())),
+///     target: DBType::Decimal { precision: Some(10), scale: Some(2) }
+/// }
+/// ```
+/// ---
+/// ```text
+/// flag IS NOT UNKNOWN
+/// ```
+/// tests a value against `NULL`, `TRUE`, `FALSE` or `UNKNOWN` (the third truth value of SQL, `Expression::Unknown`). `value` is always one of `Expression::Null`, `Expression::Bool` and `Expression::Unknown`:
+/// ```rust,ignore
+/// Expression::Is {
+///     expr: Box::new(Expression::Identifier("flag".to_string())),
+///     value: Box::new(Expression::Unknown),
+///     negated: true
+/// }
+/// ```
+/// ---
+/// ```text
+/// INTERVAL '7' DAY
+/// ```
+/// is a length of time, like in `created_at > now - INTERVAL '7' DAY`. The amount is kept as it was written (a number like `INTERVAL 7 DAY` is kept as `"7"` too): `Expression::Interval { value: "7".to_string(), unit: IntervalUnit::Day }`.
+/// ---
+/// ```text
+/// DATE '2024-01-01'
+/// ```
+/// is a typed literal, a string that is a value of the given type (`DATE`, `TIME` or `TIMESTAMP`). The text isn't checked to be a real date, it is kept as it was written: `Expression::TypedLiteral { ty: DBType::Date, value: "2024-01-01".to_string() }`. It is different from `CAST('2024-01-01' AS DATE)`, which converts a value at runtime, and from a column called `date`, which isn't followed by a string.
+/// ---
+/// ```text
+/// salary > ALL (SELECT salary FROM interns)
+/// ```
+/// compares a value with every row of a subquery: with `ALL` the comparison has to be true for all of them, with `ANY` (or its synonym `SOME`) for at least one. Only comparison operators can be used: `Expression::QuantifiedComparison { expr, op: BinaryOperator::GreaterThan, quantifier: Quantifier::All, query }`.
+/// ---
+/// ```text
+/// code NOT LIKE 'a\_%' ESCAPE '\'
+/// ```
+/// matches a string against a pattern, where `%` stands for any text and `_` for any one character. The character after `ESCAPE` (`None` when there is none) makes the `%` or `_` right after it a normal character:
+/// ```rust,ignore
+/// Expression::Like {
+///     expr: Box::new(Expression::Identifier("code".to_string())),
+///     pattern: Box::new(Expression::String("a\\_%".to_string())),
+///     negated: true,
+///     escape: Some('\\')
+/// }
+/// ```
+/// ---
+/// ```text
+/// a AND b AND c
+/// ```
+/// is parsed as two nested `BinaryOperation`s, `(a AND b) AND c`. `flatten_logical` turns such chains into one node with all of the operands, `Expression::And(vec![a, b, c])` (and the same with `Expression::Or`), which is easier to go through for tools that look at the conditions one by one. The parser itself never produces these two.
+/// ---
+/// ```text
+/// INSERT INTO t (a, b) VALUES (1, DEFAULT)
+/// ```
+/// `DEFAULT` written in place of a value is `Expression::Default`: the column gets its default value (see `Constraint::Default`), or `NULL` when it has none. It is only read among the values of `INSERT`, anywhere else `DEFAULT` is still an error.
+/// ---
+/// ```text
 /// 5 * 3 - 4 + c / (13 -)
 /// ```
 /// is a string, that, the parser should throw an error to the user when it encounters it.
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub enum Expression {
     BinaryOperation {
         left_operand: Box<Expression>,
@@ -373,10 +968,90 @@ pub enum Expression {
         operator: UnaryOperator,
     },
     Number(u64),
+    Float(FloatLiteral),
     Bool(bool),
+    Placeholder(Placeholder),
     Identifier(String),
     String(String),
+    Null,
+    Tuple(Vec<Expression>),
+    QualifiedIdentifier {
+        table: String,
+        column: String,
+    },
+    QualifiedAllColumns(String),
+    FunctionCall {
+        name: String,
+        args: Vec<Expression>,
+        distinct: bool,
+        window: Option<WindowSpec>,
+    },
+    Exists {
+        query: Box<Statement>,
+        negated: bool,
+    },
+    InList {
+        expr: Box<Expression>,
+        list: Vec<Expression>,
+        negated: bool,
+    },
+    InSubquery {
+        expr: Box<Expression>,
+        query: Box<Statement>,
+        negated: bool,
+    },
+    Cast {
+        expr: Box<Expression>,
+        target: DBType,
+    },
+    Unknown,
+    Interval {
+        value: String,
+        unit: IntervalUnit,
+    },
+    TypedLiteral {
+        ty: DBType,
+        value: String,
+    },
+    Is {
+        expr: Box<Expression>,
+        value: Box<Expression>,
+        negated: bool,
+    },
+    QuantifiedComparison {
+        expr: Box<Expression>,
+        op: BinaryOperator,
+        quantifier: Quantifier,
+        query: Box<Statement>,
+    },
+    Like {
+        expr: Box<Expression>,
+        pattern: Box<Expression>,
+        negated: bool,
+        escape: Option<char>,
+    },
+    And(Vec<Expression>),
+    Or(Vec<Expression>),
     AllColumns,  // Added variant to represent '*' -- all columns
+    Default,
+}
+
+impl Expression {
+    // The value of an integer literal, including the signed ones: -5 is parsed as a unary minus over 5,
+    // so this saves every user of a CHECK bound or a default value from unwrapping it by hand.
+    // Returns None for anything else (or a number that doesn't fit into i64)
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Expression::Number(n) => i64::try_from(*n).ok(),
+            Expression::UnaryOperation { operator: UnaryOperator::Plus, operand } => operand.as_i64(),
+            Expression::UnaryOperation { operator: UnaryOperator::Minus, operand } => match operand.as_ref() {
+                // -9223372036854775808 fits into i64 even though 9223372036854775808 itself doesn't
+                Expression::Number(n) => 0i64.checked_sub_unsigned(*n),
+                operand => operand.as_i64()?.checked_neg(),
+            },
+            _ => None,
+        }
+    }
 }
 
 
@@ -384,31 +1059,79 @@ pub enum Expression {
 /// 1. `column_name` – A simple string, representing a name.
 /// 2. `column_type` – The type of the column. Types are defined in the `DBType` enum.
 /// 3.  `constraints` – A vector of constraints on the column. Types of constraints are defined in the `Constraint` enum.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct TableColumn {
     pub column_name: String,
     pub column_type: DBType,
     pub constraints: Vec<Constraint>,
 }
 
-/// A column in the database can be any of these types. `Int` and `Bool` types have no additional info, while the `Varchar(n)` type has an additional argument – the length of the string. Adding a type, such as `DECIMAL(n, m)` is boiled down to adding tokens for that type, parsing that type and adding it to this enum.
-#[derive(Debug, PartialEq)]
+/// A column in the database can be any of these types. `Int`, `Bool`, `Float`, `Double`, `Text`, `Date`, `Time` and `Timestamp` types have no additional info, while the `Varchar(n)` and `Char(n)` types have an additional argument – the length of the string. `Decimal` can optionally have a precision and a scale (`DECIMAL(10, 2)`), both are `None` when they aren't written out. Adding a type, such as `DECIMAL(n, m)` is boiled down to adding tokens for that type, parsing that type and adding it to this enum.
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub enum DBType {
     Int,
     Varchar(usize),
     Bool,
+    Float,
+    Double,
+    Decimal {
+        precision: Option<u32>,
+        scale: Option<u32>,
+    },
+    Date,
+    Time,
+    Timestamp,
+    Text,
+    Char(usize),
 }
 
-/// A column can be limited to a domain of values, which is defined by constraints on that column. `PrimaryKey` and `NotNull` constraints have no additional info, while the `Check` constraints has an additional argument – the expression which every table row must satisfy.
-#[derive(Debug, PartialEq)]
+/// A column can be limited to a domain of values, which is defined by constraints on that column. `PrimaryKey`, `NotNull` and `Unique` constraints have no additional info, while the `Check` constraints has an additional argument – the expression which every table row must satisfy. `Default` is the value a new row gets when the column isn't given one (`DEFAULT 0`, `DEFAULT NULL`). `References` is a foreign key pointing to another table, optionally to a specific column of it (`REFERENCES users(id)`), otherwise `column` is `None`. The table can be written with its schema (`REFERENCES auth.users(id)`), so it is a `TableName`. `on_delete` and `on_update` are the `RefAction`s from `ON DELETE ...` and `ON UPDATE ...`, `None` when they aren't written. `Collate` and `CharacterSet` don't limit the values, they only name the collation (`COLLATE utf8_general_ci`) or the character set (`CHARACTER SET utf8mb4`) of a text column, but they are written among the constraints, so they are kept here. The same goes for `Generated`, a computed column (`GENERATED ALWAYS AS (qty * price)`): `expr` is the expression its value comes from, and `stored` is set for `STORED` (the value is saved with the row) and not set for `VIRTUAL` or nothing (it is computed when it is read).
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub enum Constraint {
     NotNull,
     PrimaryKey,
+    Unique,
     Check(Expression),
+    Default(Expression),
+    References {
+        table: TableName,
+        column: Option<String>,
+        on_delete: Option<RefAction>,
+        on_update: Option<RefAction>,
+    },
+    Collate(String),
+    CharacterSet(String),
+    Generated { expr: Expression, stored: bool },
 }
 
-/// Binary and unary operators are defined as enums, where each enumeration constant represents one operator. Binary and unary operators are defined separately because a `-` (minus), for example can be in a binary operation: `5 - 4`, as well as in a unary operation: `-2`. `Asc` and `Desc` are `ORDER BY` operators that have the lowest operator precedence in any expression. While both unary and binary operators may be the exact same as tokens that represent them, it is important to make a distinction between them, as they are used in different contexts.
-#[derive(Debug, PartialEq)]
+/// A constraint written as its own item in the `CREATE TABLE` list rather than after a column definition. Unlike `Constraint`, these can cover several columns at once, which is needed for composite keys like `PRIMARY KEY (a, b)`. `ForeignKey` lists the local columns, the referenced table and the referenced columns (empty when they aren't written out), and the same `ON DELETE`/`ON UPDATE` actions as `Constraint::References`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum TableConstraint {
+    PrimaryKey(Vec<String>),
+    Unique(Vec<String>),
+    Check(Expression),
+    ForeignKey {
+        columns: Vec<String>,
+        table: TableName,
+        referenced_columns: Vec<String>,
+        on_delete: Option<RefAction>,
+        on_update: Option<RefAction>,
+    },
+}
+
+/// What the database does with a row when the row its foreign key points to is deleted (`ON DELETE`) or its key is changed (`ON UPDATE`).
+/// `Cascade` deletes or updates the row too, `SetNull` and `SetDefault` change its foreign key column, and `Restrict` and `NoAction` refuse the change.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub enum RefAction {
+    Cascade,
+    SetNull,
+    SetDefault,
+    Restrict,
+    NoAction,
+}
+
+/// Binary and unary operators are defined as enums, where each enumeration constant represents one operator. Binary and unary operators are defined separately because a `-` (minus), for example can be in a binary operation: `5 - 4`, as well as in a unary operation: `-2`. `ASC` and `DESC` are not operators, they belong to an `ORDER BY` item (see `OrderByItem`). While both unary and binary operators may be the exact same as tokens that represent them, it is important to make a distinction between them, as they are used in different contexts.
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub enum BinaryOperator {
     Plus,
     Minus,
@@ -424,14 +1147,55 @@ pub enum BinaryOperator {
     Or,
 }
 
-/// Binary and unary operators are defined as enums, where each enumeration constant represents one operator. Binary and unary operators are defined separately because a `-` (minus), for example can be in a binary operation: `5 - 4`, as well as in a unary operation: `-2`. `Asc` and `Desc` are `ORDER BY` operators that have the lowest operator precedence in any expression. While both unary and binary operators may be the exact same as tokens that represent them, it is important to make a distinction between them, as they are used in different contexts.
-#[derive(Debug, PartialEq)]
+impl BinaryOperator {
+    // The precedence levels used by the parser, from the loosest (OR) to the tightest (* and /)
+    pub const OR_PRECEDENCE: u8 = 1;
+    pub const AND_PRECEDENCE: u8 = 2;
+    pub const EQUALITY_PRECEDENCE: u8 = 3;
+    pub const COMPARISON_PRECEDENCE: u8 = 4;
+    pub const ADDITIVE_PRECEDENCE: u8 = 5;
+    pub const MULTIPLICATIVE_PRECEDENCE: u8 = 6;
+
+    // Comparisons compare two values and give a boolean (=, !=, <, <=, >, >=)
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinaryOperator::Equal
+                | BinaryOperator::NotEqual
+                | BinaryOperator::LessThan
+                | BinaryOperator::LessThanOrEqual
+                | BinaryOperator::GreaterThan
+                | BinaryOperator::GreaterThanOrEqual
+        )
+    }
+
+    // Logical operators combine two booleans (AND, OR)
+    pub fn is_logical(&self) -> bool {
+        matches!(self, BinaryOperator::And | BinaryOperator::Or)
+    }
+
+    // The comparison that is true exactly when this one is false, so NOT (a < b) can be rewritten as a >= b.
+    // Arithmetic operators can't be negated, and neither can AND/OR (that needs negating the operands too),
+    // so for those this returns None
+    pub fn negate(&self) -> Option<BinaryOperator> {
+        match self {
+            BinaryOperator::Equal => Some(BinaryOperator::NotEqual),
+            BinaryOperator::NotEqual => Some(BinaryOperator::Equal),
+            BinaryOperator::LessThan => Some(BinaryOperator::GreaterThanOrEqual),
+            BinaryOperator::GreaterThanOrEqual => Some(BinaryOperator::LessThan),
+            BinaryOperator::GreaterThan => Some(BinaryOperator::LessThanOrEqual),
+            BinaryOperator::LessThanOrEqual => Some(BinaryOperator::GreaterThan),
+            _ => None,
+        }
+    }
+}
+
+/// Binary and unary operators are defined as enums, where each enumeration constant represents one operator. Binary and unary operators are defined separately because a `-` (minus), for example can be in a binary operation: `5 - 4`, as well as in a unary operation: `-2`. `ASC` and `DESC` are not operators, they belong to an `ORDER BY` item (see `OrderByItem`). While both unary and binary operators may be the exact same as tokens that represent them, it is important to make a distinction between them, as they are used in different contexts.
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub enum UnaryOperator {
     Not,
     Plus,
     Minus,
-    Asc,
-    Desc,
 }
 
 // Example manual implementations for Display traits.
@@ -444,8 +1208,6 @@ impl Display for UnaryOperator {
         match self {
             UnaryOperator::Minus => write!(f, "-"),
             UnaryOperator::Plus => write!(f, "+"),
-            UnaryOperator::Desc => write!(f, "DESC"),
-            UnaryOperator::Asc => write!(f, "ASC"),
             UnaryOperator::Not => write!(f, "NOT"),
         }
     }
@@ -474,16 +1236,777 @@ impl Display for Expression {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Expression::BinaryOperation { left_operand, operator, right_operand } => {
-                write!(f, "({:?} {:?} {:?})", left_operand, operator, right_operand)
+                // Parentheses are only written where they are needed to keep the same tree when parsing it back:
+                // a side needs them if it binds weaker than this operator, and also if it binds equally but
+                // the associativity would group it the other way (a - (b - c) is not a - b - c)
+                let prec = get_precedence(operator);
+                let assoc = associativity(operator);
+                write_operand(f, left_operand, prec, |p| p < prec || (p == prec && assoc == Associativity::Right))?;
+                write!(f, " {} ", operator)?;
+                write_operand(f, right_operand, prec, |p| p < prec || (p == prec && assoc == Associativity::Left))
             }
             Expression::UnaryOperation { operand, operator } => {
-                write!(f, "({:?} {:?})", operator, operand)
+                // A space is needed after NOT, while - and + are written right before the operand.
+                // Anything that isn't a single value gets parentheses, so -(-x) is never printed as --x
+                // (and NOT (EXISTS ...) isn't read back as NOT EXISTS)
+                match operator {
+                    UnaryOperator::Not => write!(f, "NOT ")?,
+                    _ => write!(f, "{}", operator)?,
+                }
+                match operand.as_ref() {
+                    Expression::BinaryOperation { .. }
+                    | Expression::UnaryOperation { .. }
+                    | Expression::Exists { .. }
+                    | Expression::InList { .. }
+                    | Expression::InSubquery { .. }
+                    | Expression::Is { .. }
+                    | Expression::QuantifiedComparison { .. }
+                    | Expression::Like { .. }
+                    | Expression::And(_)
+                    | Expression::Or(_) => {
+                        write!(f, "({})", operand)
+                    }
+                    _ => write!(f, "{}", operand),
+                }
             }
             Expression::Number(num) => write!(f, "{num}"),
-            Expression::Identifier(iden) => write!(f, "{}", iden),
-            Expression::String(str) => write!(f, "\"{}\"", str),
-            Expression::Bool(b) => write!(f, "{}", b),
+            // Debug always writes a '.' or an exponent (15000000000.0, 6.022e23), so it is read back as a float, not an integer
+            Expression::Float(num) => write!(f, "{:?}", num.0),
+            Expression::Identifier(iden) => write_identifier(f, iden),
+            Expression::String(str) => write!(f, "'{}'", str),
+            Expression::Placeholder(placeholder) => write!(f, "{}", placeholder),
+            Expression::Bool(true) => write!(f, "TRUE"),
+            Expression::Bool(false) => write!(f, "FALSE"),
+            Expression::Null => write!(f, "NULL"),
+            Expression::Default => write!(f, "DEFAULT"),
+            Expression::Unknown => write!(f, "UNKNOWN"),
+            Expression::Interval { value, unit } => write!(f, "INTERVAL '{}' {}", value, unit),
+            Expression::TypedLiteral { ty, value } => write!(f, "{} '{}'", ty, value),
+            Expression::Tuple(elements) => {
+                write!(f, "(")?;
+                write_list(f, elements)?;
+                write!(f, ")")
+            }
             Expression::AllColumns => write!(f, "*"),
+            Expression::QualifiedIdentifier { table, column } => {
+                write_identifier(f, table)?;
+                write!(f, ".")?;
+                write_identifier(f, column)
+            }
+            Expression::QualifiedAllColumns(table) => {
+                write_identifier(f, table)?;
+                write!(f, ".*")
+            }
+            Expression::FunctionCall { name, args, distinct, window } => {
+                write_identifier(f, name)?;
+                write!(f, "(")?;
+                if *distinct {
+                    write!(f, "DISTINCT ")?;
+                }
+                write_list(f, args)?;
+                write!(f, ")")?;
+                if let Some(window) = window {
+                    write!(f, " OVER ({})", window)?;
+                }
+                Ok(())
+            }
+            Expression::Exists { query, negated } => {
+                if *negated {
+                    write!(f, "NOT ")?;
+                }
+                write!(f, "EXISTS (")?;
+                write_query(f, query)?;
+                write!(f, ")")
+            }
+            Expression::InList { expr, list, negated } => {
+                write_in_operand(f, expr, *negated)?;
+                write!(f, "(")?;
+                write_list(f, list)?;
+                write!(f, ")")
+            }
+            Expression::InSubquery { expr, query, negated } => {
+                write_in_operand(f, expr, *negated)?;
+                write!(f, "(")?;
+                write_query(f, query)?;
+                write!(f, ")")
+            }
+            Expression::Cast { expr, target } => write!(f, "CAST({} AS {})", expr, target),
+            Expression::QuantifiedComparison { expr, op, quantifier, query } => {
+                let prec = get_precedence(op);
+                write_operand(f, expr, prec, |p| p < prec)?;
+                write!(f, " {} {} (", op, quantifier)?;
+                write_query(f, query)?;
+                write!(f, ")")
+            }
+            // LIKE groups like a left-associative comparison, so another comparison as the pattern gets parentheses
+            Expression::Like { expr, pattern, negated, escape } => {
+                write_operand(f, expr, LIKE_PRECEDENCE, |p| p < LIKE_PRECEDENCE)?;
+                write!(f, "{}", if *negated { " NOT LIKE " } else { " LIKE " })?;
+                write_operand(f, pattern, LIKE_PRECEDENCE, |p| p <= LIKE_PRECEDENCE)?;
+                if let Some(escape) = escape {
+                    write!(f, " ESCAPE '{}'", escape)?;
+                }
+                Ok(())
+            }
+            // Written like the chain of binary operations it came from, a AND b AND c
+            Expression::And(items) | Expression::Or(items) => {
+                let (operator, prec) = match self {
+                    Expression::And(_) => (BinaryOperator::And, BinaryOperator::AND_PRECEDENCE),
+                    _ => (BinaryOperator::Or, BinaryOperator::OR_PRECEDENCE),
+                };
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " {} ", operator)?;
+                    }
+                    write_operand(f, item, prec, |p| p < prec || (i > 0 && p == prec))?;
+                }
+                Ok(())
+            }
+            // IS groups like a left-associative =, so only AND, OR (and NOT) on the left need parentheses
+            Expression::Is { expr, value, negated } => {
+                write_operand(f, expr, IS_PRECEDENCE, |p| p < IS_PRECEDENCE)?;
+                if *negated {
+                    write!(f, " IS NOT {}", value)
+                } else {
+                    write!(f, " IS {}", value)
+                }
+            }
+        }
+    }
+}
+
+// Writes one side of a binary operation, wrapping it in parentheses when `needs_parens` says
+// that its operator binds too weakly to stay without them
+// A NOT operand also needs them under anything tighter than AND, otherwise NOT would take the rest of the comparison
+fn write_operand(f: &mut Formatter<'_>, operand: &Expression, parent_prec: u8, needs_parens: impl Fn(u8) -> bool) -> std::fmt::Result {
+    match operand {
+        Expression::BinaryOperation { operator, .. } if needs_parens(get_precedence(operator)) => write!(f, "({})", operand),
+        Expression::InList { .. } | Expression::InSubquery { .. } if needs_parens(IN_PRECEDENCE) => write!(f, "({})", operand),
+        Expression::Is { .. } if needs_parens(IS_PRECEDENCE) => write!(f, "({})", operand),
+        Expression::Like { .. } if needs_parens(LIKE_PRECEDENCE) => write!(f, "({})", operand),
+        Expression::And(_) if needs_parens(BinaryOperator::AND_PRECEDENCE) => write!(f, "({})", operand),
+        Expression::Or(_) if needs_parens(BinaryOperator::OR_PRECEDENCE) => write!(f, "({})", operand),
+        Expression::QuantifiedComparison { op, .. } if needs_parens(get_precedence(op)) => write!(f, "({})", operand),
+        Expression::UnaryOperation { operator: UnaryOperator::Not, .. } if parent_prec >= NOT_PRECEDENCE => write!(f, "({})", operand),
+        _ => write!(f, "{}", operand),
+    }
+}
+
+// Writes the part of an IN expression before the parentheses: `expr IN ` or `expr NOT IN `
+// IN groups like a left-associative comparison, so only a weaker operator on the left needs parentheses
+fn write_in_operand(f: &mut Formatter<'_>, expr: &Expression, negated: bool) -> std::fmt::Result {
+    write_operand(f, expr, IN_PRECEDENCE, |p| p < IN_PRECEDENCE)?;
+    if negated {
+        write!(f, " NOT IN ")
+    } else {
+        write!(f, " IN ")
+    }
+}
+
+// Identifiers are written as they are, unless they would be read back as something else
+// (a keyword, or a name with spaces or other special characters), then they are written in double quotes
+fn write_identifier(f: &mut Formatter<'_>, name: &str) -> std::fmt::Result {
+    let mut chars = name.chars();
+    let plain = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && keyword_for(name).is_none();
+    if plain {
+        write!(f, "{}", name)
+    } else {
+        write!(f, "\"{}\"", name)
+    }
+}
+
+// Writes a list of items separated by commas, like the columns of a SELECT
+fn write_list<T: Display>(f: &mut Formatter<'_>, items: &[T]) -> std::fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+// Same as write_list, but for plain names that may need quoting
+fn write_identifier_list(f: &mut Formatter<'_>, names: &[String]) -> std::fmt::Result {
+    for (i, name) in names.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write_identifier(f, name)?;
+    }
+    Ok(())
+}
+
+// The RETURNING list at the end of INSERT, UPDATE and DELETE, nothing when it is empty
+fn write_returning(f: &mut Formatter<'_>, returning: &[Expression]) -> std::fmt::Result {
+    if !returning.is_empty() {
+        write!(f, " RETURNING ")?;
+        write_list(f, returning)?;
+    }
+    Ok(())
+}
+
+// The start of CREATE TABLE and CREATE TABLE ... AS, up to the table name
+fn write_create_table(f: &mut Formatter<'_>, if_not_exists: bool) -> std::fmt::Result {
+    if if_not_exists {
+        write!(f, "CREATE TABLE IF NOT EXISTS ")
+    } else {
+        write!(f, "CREATE TABLE ")
+    }
+}
+
+// Statements are printed back as SQL, so format!("{}", statement) gives a query that parses to the same statement
+// Writes a SELECT without the semicolon at the end, the same way it is written inside a subquery
+fn write_query(f: &mut Formatter<'_>, query: &Statement) -> std::fmt::Result {
+    match query {
+        Statement::Select { columns, from, r#where, orderby, ctes, limit, group_by, with_rollup } => {
+            if !ctes.is_empty() {
+                write!(f, "WITH ")?;
+                write_list(f, ctes)?;
+                write!(f, " ")?;
+            }
+            write!(f, "SELECT ")?;
+            write_list(f, columns)?;
+            write!(f, " FROM {}", from)?;
+            if let Some(expr) = r#where {
+                write!(f, " WHERE {}", expr)?;
+            }
+            if !group_by.is_empty() {
+                write!(f, " GROUP BY ")?;
+                write_list(f, group_by)?;
+                if *with_rollup {
+                    write!(f, " WITH ROLLUP")?;
+                }
+            }
+            if !orderby.is_empty() {
+                write!(f, " ORDER BY ")?;
+                write_list(f, orderby)?;
+            }
+            if let Some(limit) = limit {
+                write!(f, " {}", limit)?;
+            }
+            Ok(())
+        }
+        // The tree only comes from the order of the operators, so writing them one after another parses back the same way
+        Statement::SetOperation { op, all, left, right } => {
+            write_query(f, left)?;
+            write!(f, " {}{} ", op, if *all { " ALL" } else { "" })?;
+            write_query(f, right)
+        }
+        // Only a query can be parsed as a subquery, anything else is written as a whole statement
+        other => write!(f, "{}", other),
+    }
+}
+
+impl Display for Statement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Statement::Select { .. } | Statement::SetOperation { .. } => {
+                write_query(f, self)?;
+                write!(f, ";")
+            }
+            Statement::CreateTable { table_name, column_list, table_constraints, if_not_exists } => {
+                write_create_table(f, *if_not_exists)?;
+                write!(f, "{} (", table_name)?;
+                write_list(f, column_list)?;
+                if !table_constraints.is_empty() {
+                    if !column_list.is_empty() {
+                        write!(f, ", ")?;
+                    }
+                    write_list(f, table_constraints)?;
+                }
+                write!(f, ");")
+            }
+            Statement::AlterTable { table_name, action } => {
+                write!(f, "ALTER TABLE {}", table_name)?;
+                match action {
+                    AlterTableAction::AddColumn(column) => write!(f, " ADD COLUMN {};", column),
+                    AlterTableAction::DropColumn { name, drop_behavior } => {
+                        write!(f, " DROP COLUMN ")?;
+                        write_identifier(f, name)?;
+                        if let Some(behavior) = drop_behavior {
+                            write!(f, " {}", behavior)?;
+                        }
+                        write!(f, ";")
+                    }
+                }
+            }
+            // The query already ends with a semicolon
+            Statement::CreateTableAs { table_name, if_not_exists, query } => {
+                write_create_table(f, *if_not_exists)?;
+                write!(f, "{} AS {}", table_name, query)
+            }
+            Statement::Truncate { table_name } => {
+                write!(f, "TRUNCATE TABLE {};", table_name)
+            }
+            Statement::CreateIndex { name, table, columns, unique } => {
+                write!(f, "CREATE {}INDEX ", if *unique { "UNIQUE " } else { "" })?;
+                write_identifier(f, name)?;
+                write!(f, " ON {} (", table)?;
+                write_list(f, columns)?;
+                write!(f, ");")
+            }
+            // The inner statement already ends with a semicolon
+            Statement::Explain { analyze, inner } => {
+                write!(f, "EXPLAIN {}{}", if *analyze { "ANALYZE " } else { "" }, inner)
+            }
+            Statement::Insert { table_name, columns, values, returning } => {
+                write!(f, "INSERT INTO {}", table_name)?;
+                if !columns.is_empty() {
+                    write!(f, " (")?;
+                    write_identifier_list(f, columns)?;
+                    write!(f, ")")?;
+                }
+                write!(f, " VALUES ")?;
+                for (i, row) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "(")?;
+                    write_list(f, row)?;
+                    write!(f, ")")?;
+                }
+                write_returning(f, returning)?;
+                write!(f, ";")
+            }
+            Statement::Update { table_name, assignments, r#where, returning } => {
+                write!(f, "UPDATE {} SET ", table_name)?;
+                write_list(f, assignments)?;
+                if let Some(expr) = r#where {
+                    write!(f, " WHERE {}", expr)?;
+                }
+                write_returning(f, returning)?;
+                write!(f, ";")
+            }
+            Statement::Delete { table_name, r#where, returning } => {
+                write!(f, "DELETE FROM {}", table_name)?;
+                if let Some(expr) = r#where {
+                    write!(f, " WHERE {}", expr)?;
+                }
+                write_returning(f, returning)?;
+                write!(f, ";")
+            }
+        }
+    }
+}
+
+impl Display for CommonTableExpression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_identifier(f, &self.name)?;
+        if !self.columns.is_empty() {
+            write!(f, " (")?;
+            write_identifier_list(f, &self.columns)?;
+            write!(f, ")")?;
+        }
+        write!(f, " AS (")?;
+        write_query(f, &self.query)?;
+        write!(f, ")")
+    }
+}
+
+impl Display for Assignment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_identifier(f, &self.column)?;
+        write!(f, " = {}", self.value)
+    }
+}
+
+// Both parts are written like any other name, so a part that is a keyword gets quoted ("public"."order")
+impl Display for TableName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(schema) = &self.schema {
+            write_identifier(f, schema)?;
+            write!(f, ".")?;
+        }
+        write_identifier(f, &self.name)
+    }
+}
+
+// The SQL words that start the statement, like CREATE TABLE
+impl Display for StatementKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatementKind::Select => write!(f, "SELECT"),
+            StatementKind::CreateTable => write!(f, "CREATE TABLE"),
+            StatementKind::AlterTable => write!(f, "ALTER TABLE"),
+            StatementKind::CreateTableAs => write!(f, "CREATE TABLE AS"),
+            StatementKind::Truncate => write!(f, "TRUNCATE"),
+            StatementKind::SetOperation => write!(f, "SET OPERATION"),
+            StatementKind::CreateIndex => write!(f, "CREATE INDEX"),
+            StatementKind::Explain => write!(f, "EXPLAIN"),
+            StatementKind::Insert => write!(f, "INSERT"),
+            StatementKind::Update => write!(f, "UPDATE"),
+            StatementKind::Delete => write!(f, "DELETE"),
+        }
+    }
+}
+
+// MINUS is written as EXCEPT, the standard name that more databases understand
+impl Display for SetOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetOp::Union => write!(f, "UNION"),
+            SetOp::Except => write!(f, "EXCEPT"),
+            SetOp::Intersect => write!(f, "INTERSECT"),
+        }
+    }
+}
+
+// Always written in the standard form, LIMIT count OFFSET offset, which every dialect can read back
+impl Display for Limit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LIMIT {}", self.count)?;
+        if let Some(offset) = &self.offset {
+            write!(f, " OFFSET {}", offset)?;
+        }
+        Ok(())
+    }
+}
+
+// Only the inside of the parentheses, PARTITION BY a ORDER BY b (nothing at all for an empty window)
+impl Display for WindowSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if !self.partition_by.is_empty() {
+            write!(f, "PARTITION BY ")?;
+            write_list(f, &self.partition_by)?;
+            if !self.order_by.is_empty() {
+                write!(f, " ")?;
+            }
+        }
+        if !self.order_by.is_empty() {
+            write!(f, "ORDER BY ")?;
+            write_list(f, &self.order_by)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for OrderByItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.expr)?;
+        match self.direction {
+            Some(OrderDirection::Asc) => write!(f, " ASC")?,
+            Some(OrderDirection::Desc) => write!(f, " DESC")?,
+            None => {}
+        }
+        match self.nulls {
+            Some(NullsOrder::First) => write!(f, " NULLS FIRST"),
+            Some(NullsOrder::Last) => write!(f, " NULLS LAST"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Display for TableColumn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_identifier(f, &self.column_name)?;
+        write!(f, " {}", self.column_type)?;
+        for constraint in &self.constraints {
+            write!(f, " {}", constraint)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Quantifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Quantifier::All => write!(f, "ALL"),
+            Quantifier::Any => write!(f, "ANY"),
+            Quantifier::Some => write!(f, "SOME"),
+        }
+    }
+}
+
+impl Display for IntervalUnit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntervalUnit::Second => write!(f, "SECOND"),
+            IntervalUnit::Minute => write!(f, "MINUTE"),
+            IntervalUnit::Hour => write!(f, "HOUR"),
+            IntervalUnit::Day => write!(f, "DAY"),
+            IntervalUnit::Month => write!(f, "MONTH"),
+            IntervalUnit::Year => write!(f, "YEAR"),
         }
     }
-}
\ No newline at end of file
+}
+
+impl Display for DBType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DBType::Int => write!(f, "INT"),
+            DBType::Varchar(len) => write!(f, "VARCHAR({})", len),
+            DBType::Bool => write!(f, "BOOL"),
+            DBType::Float => write!(f, "FLOAT"),
+            DBType::Double => write!(f, "DOUBLE"),
+            DBType::Decimal { precision: Some(p), scale: Some(s) } => write!(f, "DECIMAL({}, {})", p, s),
+            DBType::Decimal { precision: Some(p), scale: None } => write!(f, "DECIMAL({})", p),
+            DBType::Decimal { .. } => write!(f, "DECIMAL"),
+            DBType::Date => write!(f, "DATE"),
+            DBType::Time => write!(f, "TIME"),
+            DBType::Timestamp => write!(f, "TIMESTAMP"),
+            DBType::Text => write!(f, "TEXT"),
+            DBType::Char(len) => write!(f, "CHAR({})", len),
+        }
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Constraint::NotNull => write!(f, "NOT NULL"),
+            Constraint::PrimaryKey => write!(f, "PRIMARY KEY"),
+            Constraint::Unique => write!(f, "UNIQUE"),
+            Constraint::Check(expr) => write!(f, "CHECK ({})", expr),
+            Constraint::Default(expr) => write!(f, "DEFAULT {}", expr),
+            Constraint::References { table, column, on_delete, on_update } => {
+                write!(f, "REFERENCES {}", table)?;
+                if let Some(column) = column {
+                    write!(f, " (")?;
+                    write_identifier(f, column)?;
+                    write!(f, ")")?;
+                }
+                write_referential_actions(f, on_delete, on_update)
+            }
+            Constraint::Collate(collation) => {
+                write!(f, "COLLATE ")?;
+                write_identifier(f, collation)
+            }
+            Constraint::CharacterSet(charset) => {
+                write!(f, "CHARACTER SET ")?;
+                write_identifier(f, charset)
+            }
+            Constraint::Generated { expr, stored } => {
+                write!(f, "GENERATED ALWAYS AS ({})", expr)?;
+                if *stored {
+                    write!(f, " STORED")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for TableConstraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableConstraint::PrimaryKey(columns) => {
+                write!(f, "PRIMARY KEY (")?;
+                write_identifier_list(f, columns)?;
+                write!(f, ")")
+            }
+            TableConstraint::Unique(columns) => {
+                write!(f, "UNIQUE (")?;
+                write_identifier_list(f, columns)?;
+                write!(f, ")")
+            }
+            TableConstraint::Check(expr) => write!(f, "CHECK ({})", expr),
+            TableConstraint::ForeignKey { columns, table, referenced_columns, on_delete, on_update } => {
+                write!(f, "FOREIGN KEY (")?;
+                write_identifier_list(f, columns)?;
+                write!(f, ") REFERENCES {}", table)?;
+                if !referenced_columns.is_empty() {
+                    write!(f, " (")?;
+                    write_identifier_list(f, referenced_columns)?;
+                    write!(f, ")")?;
+                }
+                write_referential_actions(f, on_delete, on_update)
+            }
+        }
+    }
+}
+
+// ON DELETE is always written before ON UPDATE, the order they were written in doesn't change their meaning
+fn write_referential_actions(f: &mut Formatter<'_>, on_delete: &Option<RefAction>, on_update: &Option<RefAction>) -> std::fmt::Result {
+    if let Some(action) = on_delete {
+        write!(f, " ON DELETE {}", action)?;
+    }
+    if let Some(action) = on_update {
+        write!(f, " ON UPDATE {}", action)?;
+    }
+    Ok(())
+}
+
+impl Display for RefAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RefAction::Cascade => write!(f, "CASCADE"),
+            RefAction::SetNull => write!(f, "SET NULL"),
+            RefAction::SetDefault => write!(f, "SET DEFAULT"),
+            RefAction::Restrict => write!(f, "RESTRICT"),
+            RefAction::NoAction => write!(f, "NO ACTION"),
+        }
+    }
+}
+
+impl Display for DropBehavior {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DropBehavior::Cascade => write!(f, "CASCADE"),
+            DropBehavior::Restrict => write!(f, "RESTRICT"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::hash::{DefaultHasher, Hash, Hasher};
+    use crate::parse;
+    use super::{BinaryOperator, Expression, FloatLiteral};
+    use BinaryOperator::*;
+
+    const ALL_OPERATORS: [BinaryOperator; 12] =
+        [Plus, Minus, Multiply, Divide, GreaterThan, GreaterThanOrEqual, LessThan, LessThanOrEqual, Equal, NotEqual, And, Or];
+
+    fn hash_of(value: &impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Writing a statement back as SQL and parsing that again has to give the same statement
+    fn assert_round_trip(sql: &str) {
+        let statement = parse(sql).unwrap_or_else(|err| panic!("{} failed to parse: {}", sql, err));
+        let written = format!("{}", statement);
+        let reparsed = parse(&written).unwrap_or_else(|err| panic!("{} (written back from {}) failed to parse: {}", written, sql, err));
+        assert_eq!(statement, reparsed, "{} was written back as {}", sql, written);
+    }
+
+    #[test]
+    fn select_round_trip() {
+        for sql in [
+            "SELECT * FROM users;",
+            "SELECT id, name FROM db.users WHERE age >= 18 AND NOT banned ORDER BY name DESC, 1 LIMIT 10 OFFSET 5;",
+            "SELECT \"select\", `order` FROM \"from\" WHERE name = 'O\"Neil' OR name LIKE 'a!%' ESCAPE '!';",
+            "SELECT a - (b - c), (a + b) * c, -a, - -a, NOT NOT a FROM t;",
+            "SELECT a FROM t WHERE a IN (1, 2, 3) AND b NOT IN (SELECT b FROM u) AND c IS NOT NULL;",
+            "SELECT a FROM t WHERE EXISTS (SELECT * FROM u WHERE u.id = t.id) AND a > ALL (SELECT a FROM u);",
+            "SELECT COUNT(*), SUM(price), CAST(a AS DECIMAL(10, 2)), a::VARCHAR(20) FROM t GROUP BY a WITH ROLLUP;",
+            "SELECT DATE '2024-01-31', TIMESTAMP '2024-01-31 12:00:00', INTERVAL '3' DAY, 1.5, TRUE, NULL FROM t;",
+            "SELECT a FROM t WHERE a = ?;",
+            "WITH recent (id, total) AS (SELECT id, total FROM orders) SELECT id FROM recent;",
+            "SELECT a FROM t UNION ALL SELECT a FROM u EXCEPT SELECT a FROM v INTERSECT SELECT a FROM w;",
+            "EXPLAIN ANALYZE SELECT a FROM t;",
+        ] {
+            assert_round_trip(sql);
+        }
+    }
+
+    #[test]
+    fn create_table_round_trip() {
+        for sql in [
+            "CREATE TABLE users (id INT PRIMARY KEY, name VARCHAR(100) NOT NULL UNIQUE, created_at TIMESTAMP NOT NULL);",
+            "CREATE TABLE IF NOT EXISTS t (a INT CHECK (a > 0), b INT REFERENCES other (id) ON DELETE CASCADE, PRIMARY KEY (a, b));",
+            "CREATE TABLE t (price DECIMAL(10, 2), total DECIMAL(12, 2) GENERATED ALWAYS AS (price * 2) STORED, d DATE, tm TIME);",
+            "CREATE TABLE t (a INT, b INT, UNIQUE (a, b), CHECK (a < b), FOREIGN KEY (a) REFERENCES u (id) ON UPDATE SET NULL);",
+            "CREATE TABLE t (a INT REFERENCES s.users (id), b INT, FOREIGN KEY (b) REFERENCES s.other (x));",
+            "CREATE TABLE copy AS SELECT a, b FROM t WHERE a > 1;",
+            "CREATE UNIQUE INDEX idx_name ON users (name DESC, id);",
+        ] {
+            assert_round_trip(sql);
+        }
+    }
+
+    #[test]
+    fn alter_and_truncate_round_trip() {
+        for sql in [
+            "ALTER TABLE users ADD COLUMN email VARCHAR(255) NOT NULL;",
+            "ALTER TABLE users ADD email VARCHAR(255) UNIQUE;",
+            "ALTER TABLE users DROP COLUMN email;",
+            "ALTER TABLE users DROP COLUMN email CASCADE;",
+            "TRUNCATE TABLE users;",
+            "TRUNCATE db.users;",
+        ] {
+            assert_round_trip(sql);
+        }
+    }
+
+    #[test]
+    fn insert_update_delete_round_trip() {
+        for sql in [
+            "INSERT INTO users (id, \"order\") VALUES (1, 'a'), (?, -2.5) RETURNING id, users.*;",
+            "INSERT INTO db.users VALUES (1 + 2, NULL);",
+            "INSERT INTO t (a, b) VALUES (1, DEFAULT), (DEFAULT, 2);",
+            "UPDATE users SET name = 'x', age = age + 1 WHERE id IN (SELECT id FROM banned) RETURNING *;",
+            "DELETE FROM users WHERE NOT active RETURNING id, name;",
+            "DELETE FROM users;",
+        ] {
+            assert_round_trip(sql);
+        }
+    }
+
+    #[test]
+    fn same_query_parses_to_equal_statements_with_equal_hashes() {
+        for sql in [
+            "SELECT a, b FROM t WHERE a > 1.5 AND b = 'x' ORDER BY a;",
+            "SELECT price * 0.25, -2.5e3 FROM t WHERE price <> 1e-3;",
+            "CREATE TABLE t (a DECIMAL(10, 2) CHECK (a > 0.5), b TIMESTAMP NOT NULL);",
+        ] {
+            let (first, second) = (parse(sql).unwrap(), parse(sql).unwrap());
+            assert_eq!(first, second);
+            assert_eq!(hash_of(&first), hash_of(&second));
+        }
+        assert_ne!(parse("SELECT 1.5 FROM t;").unwrap(), parse("SELECT 2.5 FROM t;").unwrap());
+    }
+
+    #[test]
+    fn float_literals_compare_by_their_bits() {
+        let float = |value: f64| Expression::Float(FloatLiteral(value));
+        assert_eq!(float(0.1 + 0.2), float(0.1 + 0.2));
+        assert_eq!(hash_of(&float(0.1 + 0.2)), hash_of(&float(0.1 + 0.2)));
+        assert_ne!(float(0.1 + 0.2), float(0.3));
+        assert_eq!(float(f64::NAN), float(f64::NAN));
+        assert_eq!(hash_of(&float(f64::NAN)), hash_of(&float(f64::NAN)));
+        assert_ne!(float(0.0), float(-0.0));
+
+        let set: HashSet<Expression> = [float(1.5), float(1.5), float(f64::NAN), float(f64::NAN)].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn only_the_six_comparisons_are_comparisons() {
+        let comparisons: Vec<&BinaryOperator> = ALL_OPERATORS.iter().filter(|op| op.is_comparison()).collect();
+        assert_eq!(comparisons, [&GreaterThan, &GreaterThanOrEqual, &LessThan, &LessThanOrEqual, &Equal, &NotEqual]);
+    }
+
+    #[test]
+    fn only_and_or_are_logical() {
+        let logical: Vec<&BinaryOperator> = ALL_OPERATORS.iter().filter(|op| op.is_logical()).collect();
+        assert_eq!(logical, [&And, &Or]);
+    }
+
+    #[test]
+    fn negate_equal_is_not_equal() {
+        assert_eq!(Equal.negate(), Some(NotEqual));
+        assert_eq!(NotEqual.negate(), Some(Equal));
+    }
+
+    #[test]
+    fn negate_less_than_is_greater_than_or_equal() {
+        assert_eq!(LessThan.negate(), Some(GreaterThanOrEqual));
+        assert_eq!(GreaterThanOrEqual.negate(), Some(LessThan));
+    }
+
+    #[test]
+    fn negate_greater_than_is_less_than_or_equal() {
+        assert_eq!(GreaterThan.negate(), Some(LessThanOrEqual));
+        assert_eq!(LessThanOrEqual.negate(), Some(GreaterThan));
+    }
+
+    #[test]
+    fn arithmetic_and_logical_operators_cant_be_negated() {
+        for op in [Plus, Minus, Multiply, Divide, And, Or] {
+            assert_eq!(op.negate(), None, "{:?}", op);
+        }
+    }
+
+    #[test]
+    fn negating_twice_gives_the_same_comparison() {
+        for op in ALL_OPERATORS.iter().filter(|op| op.is_comparison()) {
+            assert_eq!(op.negate().and_then(|negated| negated.negate()).as_ref(), Some(op));
+        }
+    }
+}
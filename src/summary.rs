@@ -0,0 +1,26 @@
+use crate::statement::StatementKind;
+use crate::token::Token;
+use crate::tokenizer::tokenize;
+
+/// A short description of a script, without the parsed statements themselves (see `summarize`).
+/// 1. `statement_count` – How many statements the script has.
+/// 2. `token_count` – How many tokens the script has, not counting comments and the end of the input.
+/// 3. `kinds` – The kind of every statement, in the order they are written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub statement_count: usize,
+    pub token_count: usize,
+    pub kinds: Vec<StatementKind>,
+}
+
+// Parses a whole script (like parse_many) but only tells how big it is and which statements it has,
+// for logging and metrics where the AST itself isn't needed. The error is the message of the first problem
+pub fn summarize(input: &str) -> Result<Summary, String> {
+    let tokens = tokenize(input).map_err(|error| error.to_string())?;
+    let statements = crate::parse_many(input).map_err(|error| error.to_string())?;
+    Ok(Summary {
+        statement_count: statements.len(),
+        token_count: tokens.iter().filter(|token| **token != Token::Eof).count(),
+        kinds: statements.iter().map(|statement| statement.kind()).collect(),
+    })
+}
@@ -1,11 +1,19 @@
+use std::borrow::Cow;
 use std::fmt::{Debug, Display, Formatter};
+use crate::statement::BinaryOperator;
 
+// Identifiers and strings borrow their text from the input string when possible (Cow::Borrowed),
+// and only own it when the text had to be changed while tokenizing, so tokens usually don't allocate
 #[derive(PartialEq, Clone, Debug)]
-pub enum Token {
+pub enum Token<'a> {
     Keyword(Keyword),
-    Identifier(String),
-    String(String),
+    Identifier(Cow<'a, str>),
+    String(Cow<'a, str>),
     Number(u64),
+    Float(f64),
+    Placeholder(Placeholder),
+    // Only produced when comments are kept (see tokenize_with_comments), the text is without the -- or /* */
+    Comment(Cow<'a, str>),
     Invalid(char),
     RightParentheses,
     LeftParentheses,
@@ -20,10 +28,39 @@ pub enum Token {
     Minus,
     Plus,
     Comma,
+    Dot,
+    DoubleColon,
     Semicolon,
     Eof,
 }
 
+// A parameter of a prepared statement, its value is given later when the statement is run.
+// `?` and `$1` are positional (a `?` gets the next number, starting from 1), `:name` is named
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum Placeholder {
+    Positional(usize),
+    Named(String),
+}
+
+// Where a token is in the input, as a range of byte offsets (the end is not included), so &input[span.start..span.end]
+// is exactly the text of the token. Byte offsets are cheap to keep for every token, line_and_column turns them
+// into something a person can find in an editor
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    // The line and column (both starting from 1) where the span starts, columns are counted in characters
+    pub fn line_and_column(&self, input: &str) -> (usize, usize) {
+        let before = &input[..self.start.min(input.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (line, before[line_start..].chars().count() + 1)
+    }
+}
+
 #[derive(PartialEq, Clone, Debug)]
 pub enum Keyword {
     Select,
@@ -47,31 +84,174 @@ pub enum Keyword {
     Bool,
     Varchar,
     Null,
+    Unique,
+    Foreign,
+    References,
+    Float,
+    Double,
+    Decimal,
+    Date,
+    Time,
+    Timestamp,
+    Text,
+    Char,
+    Alter,
+    Add,
+    Drop,
+    Column,
+    As,
+    Nulls,
+    First,
+    Last,
+    Distinct,
+    Exists,
+    With,
+    In,
+    Limit,
+    Offset,
+    Cast,
+    Truncate,
+    If,
+    Is,
+    Unknown,
+    Interval,
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+    All,
+    Any,
+    Some,
+    On,
+    Delete,
+    Update,
+    Cascade,
+    Restrict,
+    Set,
+    No,
+    Action,
+    Default,
+    Collate,
+    Character,
+    Like,
+    Escape,
+    Group,
+    Rollup,
+    Over,
+    Partition,
+    Union,
+    Except,
+    Intersect,
+    Minus,
+    Index,
+    Generated,
+    Always,
+    Stored,
+    Virtual,
+    Explain,
+    Analyze,
+    Insert,
+    Into,
+    Values,
+    Returning,
+}
+
+impl Token<'_> {
+    // Turns the token into one that owns all of its text, so it doesn't depend on the input string anymore
+    // This is needed when a token has to be kept longer than the input, for example inside an error
+    pub fn into_owned(self) -> Token<'static> {
+        match self {
+            Token::Keyword(keyword) => Token::Keyword(keyword),
+            Token::Identifier(iden) => Token::Identifier(Cow::Owned(iden.into_owned())),
+            Token::String(str) => Token::String(Cow::Owned(str.into_owned())),
+            Token::Number(num) => Token::Number(num),
+            Token::Float(num) => Token::Float(num),
+            Token::Placeholder(placeholder) => Token::Placeholder(placeholder),
+            Token::Comment(text) => Token::Comment(Cow::Owned(text.into_owned())),
+            Token::Invalid(c) => Token::Invalid(c),
+            Token::RightParentheses => Token::RightParentheses,
+            Token::LeftParentheses => Token::LeftParentheses,
+            Token::GreaterThan => Token::GreaterThan,
+            Token::GreaterThanOrEqual => Token::GreaterThanOrEqual,
+            Token::LessThan => Token::LessThan,
+            Token::LessThanOrEqual => Token::LessThanOrEqual,
+            Token::Equal => Token::Equal,
+            Token::NotEqual => Token::NotEqual,
+            Token::Star => Token::Star,
+            Token::Divide => Token::Divide,
+            Token::Minus => Token::Minus,
+            Token::Plus => Token::Plus,
+            Token::Comma => Token::Comma,
+            Token::Dot => Token::Dot,
+            Token::DoubleColon => Token::DoubleColon,
+            Token::Semicolon => Token::Semicolon,
+            Token::Eof => Token::Eof,
+        }
+    }
+
+    // The binary operator this token stands for (+, =, AND, ...), or None if it isn't one.
+    // * is always Multiply here, it only means "all columns" where the parser expects a column
+    pub fn as_binary_operator(&self) -> Option<BinaryOperator> {
+        match self {
+            Token::Equal => Some(BinaryOperator::Equal),
+            Token::NotEqual => Some(BinaryOperator::NotEqual),
+            Token::GreaterThan => Some(BinaryOperator::GreaterThan),
+            Token::GreaterThanOrEqual => Some(BinaryOperator::GreaterThanOrEqual),
+            Token::LessThan => Some(BinaryOperator::LessThan),
+            Token::LessThanOrEqual => Some(BinaryOperator::LessThanOrEqual),
+            Token::Plus => Some(BinaryOperator::Plus),
+            Token::Minus => Some(BinaryOperator::Minus),
+            Token::Star => Some(BinaryOperator::Multiply),
+            Token::Divide => Some(BinaryOperator::Divide),
+            Token::Keyword(Keyword::And) => Some(BinaryOperator::And),
+            Token::Keyword(Keyword::Or) => Some(BinaryOperator::Or),
+            _ => None,
+        }
+    }
+}
+
+// Describes the token for a person reading an error message, like keyword 'FROM', identifier 'x' or ','
+// (Debug is still there for looking at the tokens themselves)
+impl Display for Token<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Keyword(keyword) => write!(f, "keyword '{}'", keyword),
+            Token::Identifier(iden) => write!(f, "identifier '{}'", iden),
+            Token::String(str) => write!(f, "string '{}'", str),
+            Token::Number(num) => write!(f, "number {}", num),
+            Token::Float(num) => write!(f, "number {:?}", num),
+            Token::Placeholder(placeholder) => write!(f, "placeholder {}", placeholder),
+            Token::Comment(text) => write!(f, "comment '{}'", text),
+            Token::RightParentheses => write!(f, "')'"),
+            Token::LeftParentheses => write!(f, "'('"),
+            Token::GreaterThan => write!(f, "'>'"),
+            Token::GreaterThanOrEqual => write!(f, "'>='"),
+            Token::LessThan => write!(f, "'<'"),
+            Token::LessThanOrEqual => write!(f, "'<='"),
+            Token::Equal => write!(f, "'='"),
+            Token::NotEqual => write!(f, "'!='"),
+            Token::Star => write!(f, "'*'"),
+            Token::Divide => write!(f, "'/'"),
+            Token::Minus => write!(f, "'-'"),
+            Token::Plus => write!(f, "'+'"),
+            Token::Comma => write!(f, "','"),
+            Token::Dot => write!(f, "'.'"),
+            Token::DoubleColon => write!(f, "'::'"),
+            Token::Semicolon => write!(f, "';'"),
+            Token::Eof => write!(f, "end of input"),
+            Token::Invalid(c) => write!(f, "invalid character '{}'", c),
+        }
+    }
 }
 
-impl Display for Token {
+// A positional placeholder is always written with its number ($2), so the same number is read back even if it was a ?
+impl Display for Placeholder {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Token::Keyword(keyword) => write!(f, "{}", keyword),
-            Token::Identifier(iden) => write!(f, "{:?}", iden),
-            Token::String(str) => write!(f, "{:?}", str),
-            Token::Number(num) => write!(f, "{:?}", num),
-            Token::RightParentheses => write!(f, ")"),
-            Token::LeftParentheses => write!(f, "("),
-            Token::GreaterThan => write!(f, ">"),
-            Token::GreaterThanOrEqual => write!(f, ">="),
-            Token::LessThan => write!(f, "<"),
-            Token::LessThanOrEqual => write!(f, "<="),
-            Token::Equal => write!(f, "="),
-            Token::NotEqual => write!(f, "!="),
-            Token::Star => write!(f, "*"),
-            Token::Divide => write!(f, "/"),
-            Token::Minus => write!(f, "-"),
-            Token::Plus => write!(f, "+"),
-            Token::Comma => write!(f, ","),
-            Token::Semicolon => write!(f, ";"),
-            Token::Eof => write!(f, "Eof"),
-            Token::Invalid(c) => write!(f, "Invalid({})", c),
+            Placeholder::Positional(index) => write!(f, "${}", index),
+            Placeholder::Named(name) => write!(f, ":{}", name),
         }
     }
 }
@@ -100,6 +280,78 @@ impl Display for Keyword {
             Keyword::Bool => write!(f, "BOOL"),
             Keyword::Varchar => write!(f, "VARCHAR"),
             Keyword::Null => write!(f, "NULL"),
+            Keyword::Unique => write!(f, "UNIQUE"),
+            Keyword::Foreign => write!(f, "FOREIGN"),
+            Keyword::References => write!(f, "REFERENCES"),
+            Keyword::Float => write!(f, "FLOAT"),
+            Keyword::Double => write!(f, "DOUBLE"),
+            Keyword::Decimal => write!(f, "DECIMAL"),
+            Keyword::Date => write!(f, "DATE"),
+            Keyword::Time => write!(f, "TIME"),
+            Keyword::Timestamp => write!(f, "TIMESTAMP"),
+            Keyword::Text => write!(f, "TEXT"),
+            Keyword::Char => write!(f, "CHAR"),
+            Keyword::Alter => write!(f, "ALTER"),
+            Keyword::Add => write!(f, "ADD"),
+            Keyword::Drop => write!(f, "DROP"),
+            Keyword::Column => write!(f, "COLUMN"),
+            Keyword::As => write!(f, "AS"),
+            Keyword::Nulls => write!(f, "NULLS"),
+            Keyword::First => write!(f, "FIRST"),
+            Keyword::Last => write!(f, "LAST"),
+            Keyword::Distinct => write!(f, "DISTINCT"),
+            Keyword::Exists => write!(f, "EXISTS"),
+            Keyword::With => write!(f, "WITH"),
+            Keyword::In => write!(f, "IN"),
+            Keyword::Limit => write!(f, "LIMIT"),
+            Keyword::Offset => write!(f, "OFFSET"),
+            Keyword::Cast => write!(f, "CAST"),
+            Keyword::Truncate => write!(f, "TRUNCATE"),
+            Keyword::If => write!(f, "IF"),
+            Keyword::Is => write!(f, "IS"),
+            Keyword::Unknown => write!(f, "UNKNOWN"),
+            Keyword::Interval => write!(f, "INTERVAL"),
+            Keyword::Second => write!(f, "SECOND"),
+            Keyword::Minute => write!(f, "MINUTE"),
+            Keyword::Hour => write!(f, "HOUR"),
+            Keyword::Day => write!(f, "DAY"),
+            Keyword::Month => write!(f, "MONTH"),
+            Keyword::Year => write!(f, "YEAR"),
+            Keyword::All => write!(f, "ALL"),
+            Keyword::Any => write!(f, "ANY"),
+            Keyword::Some => write!(f, "SOME"),
+            Keyword::On => write!(f, "ON"),
+            Keyword::Delete => write!(f, "DELETE"),
+            Keyword::Update => write!(f, "UPDATE"),
+            Keyword::Cascade => write!(f, "CASCADE"),
+            Keyword::Restrict => write!(f, "RESTRICT"),
+            Keyword::Set => write!(f, "SET"),
+            Keyword::No => write!(f, "NO"),
+            Keyword::Action => write!(f, "ACTION"),
+            Keyword::Default => write!(f, "DEFAULT"),
+            Keyword::Collate => write!(f, "COLLATE"),
+            Keyword::Character => write!(f, "CHARACTER"),
+            Keyword::Like => write!(f, "LIKE"),
+            Keyword::Escape => write!(f, "ESCAPE"),
+            Keyword::Group => write!(f, "GROUP"),
+            Keyword::Rollup => write!(f, "ROLLUP"),
+            Keyword::Over => write!(f, "OVER"),
+            Keyword::Partition => write!(f, "PARTITION"),
+            Keyword::Union => write!(f, "UNION"),
+            Keyword::Except => write!(f, "EXCEPT"),
+            Keyword::Intersect => write!(f, "INTERSECT"),
+            Keyword::Minus => write!(f, "MINUS"),
+            Keyword::Index => write!(f, "INDEX"),
+            Keyword::Generated => write!(f, "GENERATED"),
+            Keyword::Always => write!(f, "ALWAYS"),
+            Keyword::Stored => write!(f, "STORED"),
+            Keyword::Virtual => write!(f, "VIRTUAL"),
+            Keyword::Explain => write!(f, "EXPLAIN"),
+            Keyword::Analyze => write!(f, "ANALYZE"),
+            Keyword::Insert => write!(f, "INSERT"),
+            Keyword::Into => write!(f, "INTO"),
+            Keyword::Values => write!(f, "VALUES"),
+            Keyword::Returning => write!(f, "RETURNING"),
         }
     }
 }
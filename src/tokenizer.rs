@@ -1,132 +1,474 @@
-use crate::token::{Token, Keyword};
+use std::borrow::Cow;
+use std::iter::Peekable;
+use std::str::CharIndices;
+use crate::token::{Token, Keyword, Placeholder, Span};
+use crate::error::TokenizeError;
+use crate::dialect::Dialect;
+use std::num::IntErrorKind;
 
-pub fn tokenize(input: &str) -> Result<Vec<Token>, String> {
-    let mut chars = input.chars().peekable();
-    let mut tokens = Vec::new();
+// Identifiers and strings in the returned tokens borrow their text from `input` instead of copying it,
+// so the tokens can't outlive the input string (use Token::into_owned if they have to)
+// This collects everything the Tokenizer produces into a vector, ending with Token::Eof
+pub fn tokenize(input: &str) -> Result<Vec<Token<'_>>, TokenizeError> {
+    // Most tokens are a few characters long, so this is a rough guess that saves reallocating the vector many times
+    let mut tokens = Vec::with_capacity(input.len() / 4);
+    for token in Tokenizer::new(input) {
+        tokens.push(token?);
+    }
+    Ok(tokens)
+}
 
-    while let Some(&ch) = chars.peek() {
-        match ch {
-            ' ' | '\t' | '\n' => {
-                chars.next();
-            }
-            '(' => { chars.next(); tokens.push(Token::LeftParentheses); }
-            ')' => { chars.next(); tokens.push(Token::RightParentheses); }
-            ',' => { chars.next(); tokens.push(Token::Comma); }
-            ';' => { chars.next(); tokens.push(Token::Semicolon); }
-            '+' => { chars.next(); tokens.push(Token::Plus); }
-            '-' => { chars.next(); tokens.push(Token::Minus); }
-            '*' => { chars.next(); tokens.push(Token::Star); }
-            '/' => { chars.next(); tokens.push(Token::Divide); }
-            '=' => {
-                chars.next();
-                if matches!(chars.peek(), Some('=')) { chars.next(); } //if we have = after this character,
-                tokens.push(Token::Equal); //it thinks like it is ==, adds Token::Equal
+// The same as tokenize, but every token comes together with its place in the input (see Span),
+// the parser uses them to tell where an error is
+pub fn tokenize_with_spans(input: &str) -> Result<Vec<(Token<'_>, Span)>, TokenizeError> {
+    let mut tokens = Vec::with_capacity(input.len() / 4);
+    let mut tokenizer = Tokenizer::new(input);
+    while let Some(token) = tokenizer.next_spanned() {
+        tokens.push(token?);
+    }
+    Ok(tokens)
+}
+
+// The same as tokenize, but comments are returned as Token::Comment instead of being skipped,
+// so a tool like a formatter can put them back where they were. Parser ignores these tokens
+pub fn tokenize_with_comments(input: &str) -> Result<Vec<Token<'_>>, TokenizeError> {
+    Tokenizer::new(input).with_comments(true).collect()
+}
+
+// Every token (comments included) with the byte offsets where it starts and ends, for tools like syntax highlighters:
+// &input[start..end] is exactly the text of the token, with the quotes of strings and the -- or /* */ of comments.
+// The last one is Token::Eof with an empty range at the end of the input. The error is the tokenizer's message
+pub fn tokenize_debug(input: &str) -> Result<Vec<(Token<'_>, usize, usize)>, String> {
+    let mut tokens = Vec::with_capacity(input.len() / 4);
+    let mut tokenizer = Tokenizer::new(input).with_comments(true);
+    while let Some(token) = tokenizer.next_spanned() {
+        let (token, span) = token.map_err(|error| error.to_string())?;
+        tokens.push((token, span.start, span.end));
+    }
+    Ok(tokens)
+}
+
+// The Tokenizer reads the input lazily: every call to next() reads just enough characters for one token.
+// This way very long scripts can be processed token by token without keeping all tokens in memory.
+// The last token it returns is Token::Eof, after that (or after the first error) it returns None
+pub struct Tokenizer<'a> {
+    input: &'a str,
+    // Together with each character we get its byte position, which tells us where identifiers and strings start and end
+    chars: Peekable<CharIndices<'a>>,
+    // One buffer is reused for collecting the digits of every number, so numbers don't need a new String at all
+    buffer: String,
+    finished: bool,
+    // How many ? placeholders we have seen, each one gets the next number
+    positional_count: usize,
+    dialect: Dialect,
+    // Comments are skipped like whitespace unless this is set
+    keep_comments: bool,
+    // The byte offset where the last returned token starts
+    token_start: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Tokenizer {
+            input,
+            chars: input.char_indices().peekable(),
+            buffer: String::new(),
+            finished: false,
+            positional_count: 0,
+            dialect: Dialect::default(),
+            keep_comments: false,
+            token_start: 0,
+        }
+    }
+
+    // Like next(), but also returns where the token is in the input. It ends right before the next character we haven't read
+    pub fn next_spanned(&mut self) -> Option<Result<(Token<'a>, Span), TokenizeError>> {
+        let token = self.next()?;
+        let end = self.chars.peek().map_or(self.input.len(), |&(i, _)| i);
+        Some(token.map(|token| (token, Span { start: self.token_start, end })))
+    }
+
+    // Returns comments as Token::Comment instead of skipping them
+    pub fn with_comments(mut self, keep_comments: bool) -> Self {
+        self.keep_comments = keep_comments;
+        self
+    }
+
+    // Tokenizes for one SQL dialect, for example backtick identifiers are an error in Dialect::Ansi
+    pub fn with_dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    // Reads the next token, skipping the whitespace before it. At the end of the input it returns Token::Eof
+    // It must not panic on any input, whatever can't be read is a TokenizeError or a Token::Invalid. The input is only
+    // sliced at offsets given by char_indices, or right after a one-byte ASCII character (start + 1 after '-', start + 2
+    // after "/*"), so a slice never cuts a multi-byte character in half. Keep it that way when adding a new branch
+    fn next_token(&mut self) -> Result<Token<'a>, TokenizeError> {
+        let input = self.input;
+        let chars = &mut self.chars;
+        // Set after the N of N'...', so the string that follows keeps the N in its span
+        let mut prefixed = false;
+
+        while let Some(&(start, ch)) = chars.peek() {
+            if !std::mem::take(&mut prefixed) {
+                self.token_start = start;
             }
-            '!' => {
-                chars.next();
-                if matches!(chars.peek(), Some('=')) { //if we have =, it will return as != (not equal)
+            match ch {
+                // \r is there for Windows line endings (\r\n), otherwise a query saved on Windows is full of invalid characters
+                ' ' | '\t' | '\n' | '\r' => {
                     chars.next();
-                    tokens.push(Token::NotEqual);
-                } else { //if we have single ! character, it returns an error
-                    return Err("Unexpected character '!'".into());
                 }
-            }
-            '>' => {
-                chars.next();
-                if matches!(chars.peek(), Some('=')) { // we check it is >= or just >
+                '(' => { chars.next(); return Ok(Token::LeftParentheses); }
+                ')' => { chars.next(); return Ok(Token::RightParentheses); }
+                ',' => { chars.next(); return Ok(Token::Comma); }
+                '.' => { chars.next(); return Ok(Token::Dot); }
+                //Placeholders for prepared statements: ? is numbered automatically, $1 has its own number and :name has a name
+                '?' => {
                     chars.next();
-                    tokens.push(Token::GreaterThanOrEqual); // if it is >=, it returns as GreaterThanOrEqual
-                } else {
-                    tokens.push(Token::GreaterThan); //if it is single >, so it is greaterThan
+                    self.positional_count += 1;
+                    return Ok(Token::Placeholder(Placeholder::Positional(self.positional_count)));
                 }
-            }
-            '<' => {
-                chars.next();
-                if matches!(chars.peek(), Some('=')) { // we check it is <= or just <
+                // :: is the PostgreSQL cast (price::INT), a single : starts a named placeholder
+                ':' if input[start + 1..].starts_with(':') => {
+                    chars.next();
                     chars.next();
-                    tokens.push(Token::LessThanOrEqual); // if it is >=, it returns as LessThanOrEqual
-                } else {
-                    tokens.push(Token::LessThan); //if it is single <, so it is LessThan
+                    return Ok(Token::DoubleColon);
                 }
-            }
-            '"' | '\'' => {
-                let quote = chars.next().unwrap();
-                let mut string = String::new();
-                let mut terminated = false;
-
-                while let Some(&next_ch) = chars.peek() {
+                '$' | ':' => {
+                    chars.next();
+                    let rest_start = start + 1;
+                    let rest_end = input[rest_start..]
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .map_or(input.len(), |i| rest_start + i);
+                    let rest = &input[rest_start..rest_end];
+                    let placeholder = match ch {
+                        '$' if !rest.is_empty() && rest.bytes().all(|b| b.is_ascii_digit()) => match rest.parse::<usize>() {
+                            Ok(index) => Placeholder::Positional(index),
+                            Err(_) => {
+                                return Err(TokenizeError::IntegerTooLarge { digits: rest.to_string(), position: char_position(input, rest_start) });
+                            }
+                        },
+                        ':' if rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') => Placeholder::Named(rest.to_string()),
+                        _ => return Err(TokenizeError::UnexpectedCharacter { character: ch, position: char_position(input, start) }),
+                    };
+                    //The name or number was only looked at in the input, now we skip over it
+                    while matches!(chars.peek(), Some(&(i, _)) if i < rest_end) {
+                        chars.next();
+                    }
+                    return Ok(Token::Placeholder(placeholder));
+                }
+                ';' => { chars.next(); return Ok(Token::Semicolon); }
+                '+' => { chars.next(); return Ok(Token::Plus); }
+                // -- starts a comment that goes until the end of the line
+                // Comments are checked here, before any operator is started, so a comment between two operators
+                // (a > /* x */ = b) always splits them into separate tokens, the same as a space would
+                '-' if input[start + 1..].starts_with('-') => {
+                    let end = input[start..].find('\n').map_or(input.len(), |i| start + i);
+                    // With \r\n line endings the \r is not a part of the comment, it is skipped as whitespace after it
+                    let end = if input[start + 2..end].ends_with('\r') { end - 1 } else { end };
+                    while matches!(chars.peek(), Some(&(i, _)) if i < end) {
+                        chars.next();
+                    }
+                    if self.keep_comments {
+                        return Ok(Token::Comment(Cow::Borrowed(&input[start + 2..end])));
+                    }
+                }
+                // /* starts a comment that can span several lines, it ends at the first */ (they can't be nested)
+                '/' if input[start + 1..].starts_with('*') => {
+                    let Some(length) = input[start + 2..].find("*/") else {
+                        return Err(TokenizeError::UnterminatedComment { position: char_position(input, start) });
+                    };
+                    let end = start + 2 + length;
+                    while matches!(chars.peek(), Some(&(i, _)) if i < end + 2) {
+                        chars.next();
+                    }
+                    if self.keep_comments {
+                        return Ok(Token::Comment(Cow::Borrowed(&input[start + 2..end])));
+                    }
+                }
+                '-' => { chars.next(); return Ok(Token::Minus); }
+                '*' => { chars.next(); return Ok(Token::Star); }
+                '/' => { chars.next(); return Ok(Token::Divide); }
+                '=' => {
+                    chars.next();
+                    if matches!(chars.peek(), Some((_, '='))) { chars.next(); } //if we have = after this character,
+                    return Ok(Token::Equal); //it thinks like it is ==, adds Token::Equal
+                }
+                '!' => {
+                    chars.next();
+                    if matches!(chars.peek(), Some((_, '='))) { //if we have =, it will return as != (not equal)
+                        chars.next();
+                        return Ok(Token::NotEqual);
+                    } else { //if we have single ! character, it returns an error
+                        return Err(TokenizeError::UnexpectedCharacter { character: '!', position: char_position(input, start) });
+                    }
+                }
+                '>' => {
                     chars.next();
-                    //it will be something like Some('a').
-                    if next_ch == quote { //If the character is the same as the starting quote (e.g. ' or "
-                        terminated = true;  //it means the string is finished
-                        break; //We set terminated = true and exit the loop.
+                    if matches!(chars.peek(), Some((_, '='))) { // we check it is >= or just >
+                        chars.next();
+                        return Ok(Token::GreaterThanOrEqual); // if it is >=, it returns as GreaterThanOrEqual
                     } else {
-                        string.push(next_ch); //If it’s not the ending quote, we add the character to the string we’re building.
+                        return Ok(Token::GreaterThan); //if it is single >, so it is greaterThan
                     }
                 }
-
-                if !terminated { //After the loop: if we didn’t find the closing quote, we return an error
-                    return Err(format!("Unterminated string starting with {}{}", quote, string));
+                '<' => {
+                    chars.next();
+                    if matches!(chars.peek(), Some((_, '='))) { // we check it is <= or just <
+                        chars.next();
+                        return Ok(Token::LessThanOrEqual); // if it is >=, it returns as LessThanOrEqual
+                    } else if matches!(chars.peek(), Some((_, '>'))) { // <> is the standard SQL way of writing !=
+                        chars.next();
+                        return Ok(Token::NotEqual);
+                    } else {
+                        return Ok(Token::LessThan); //if it is single <, so it is LessThan
+                    }
+                }
+                // N'text' is a national character string (text in the database's Unicode character set). Our strings can hold
+                // any text anyway, so it is read as a normal string: the N is skipped and the string is read in the next round.
+                // An N that isn't right before a quote (N, Name, N 'x') is the start of an identifier as usual
+                'N' | 'n' if input[start + 1..].starts_with('\'') => {
+                    chars.next();
+                    prefixed = true;
                 }
+                // Single quotes start a string literal, while double quotes start a quoted identifier like "user name"
+                // Backticks are the MySQL way of quoting identifiers (`my table`), so they work like double quotes
+                // (only in the dialects that allow them). All of them are read the same way, only the resulting token is different
+                '`' if !self.dialect.supports_backtick_identifiers() => {
+                    return Err(TokenizeError::UnexpectedCharacter { character: ch, position: char_position(input, start) });
+                }
+                '"' | '`' | '\'' => {
+                    let quote = ch;
+                    chars.next();
+                    let content_start = start + quote.len_utf8(); //The text starts right after the opening quote
+                    let mut content_end = None;
 
-                tokens.push(Token::String(string)); //If everything went well, we add the completed string as a token.
-            }
-            c if c.is_ascii_digit() => {
-                let mut num = String::new(); //We create an empty string called num
-                while let Some(&c) = chars.peek() { //We keep peeking and reading characters as long as they’re digits
-                    if c.is_ascii_digit() {
-                        num.push(c);
-                        chars.next();
+                    for (i, next_ch) in chars.by_ref() {
+                        //it will be something like (5, 'a').
+                        if next_ch == quote { //If the character is the same as the starting quote (e.g. ' or "
+                            content_end = Some(i);  //it means the string is finished, and it ends right before this quote
+                            break; //We remember where it ends and exit the loop.
+                        }
+                        //If it’s not the ending quote, we just keep going, the text is taken from the input at the end
+                    }
+
+                    //After the loop: if we didn’t find the closing quote, we return an error
+                    let Some(content_end) = content_end else {
+                        let content = input[content_start..].to_string();
+                        let position = char_position(input, start); //The error points at the opening quote
+                        if quote != '\'' {
+                            return Err(TokenizeError::UnterminatedIdentifier { quote, content, position });
+                        }
+                        return Err(TokenizeError::UnterminatedString { quote, content, position });
+                    };
+
+                    //If everything went well, we add the completed string as a token, borrowing the text between the quotes.
+                    //A quoted identifier keeps its exact case and is never checked against the keywords
+                    let text = Cow::Borrowed(&input[content_start..content_end]);
+                    if quote != '\'' {
+                        return Ok(Token::Identifier(text));
                     } else {
-                        break;
+                        return Ok(Token::String(text));
                     }
                 }
-                //After collecting the digits, it converts the string (e.g. "123") into a number (u64)
-                let parsed = num.parse::<u64>().map_err(|_| "Invalid number".to_string())?;
-                tokens.push(Token::Number(parsed));
-            }
+                c if c.is_ascii_digit() => {
+                    let num = &mut self.buffer; //We take the shared buffer (emptied first) and call it num
+                    num.clear();
+                    let position = char_position(input, start); //Errors about the number point at its first digit
 
-            //This block handles identifiers (e.g., variable names, function names) and keywords (e.g., SELECT, FROM, etc.) in the input.
-            //It checks if the current character is alphabetic (a letter) or an underscore (_)
-            c if c.is_ascii_alphabetic() || c == '_' => {
-                let mut ident = String::new(); //An empty string ident is created to collect the characters that form the identifier
-                while let Some(&c) = chars.peek() { //The loop checks the next character and adds it to ident as long as it’s either
-                    if c.is_ascii_alphanumeric() || c == '_' { //it can be letter,digit or underscore
-                        ident.push(c);
+                    //A number starting with 0x or 0b is a hexadecimal or a binary literal (0x1F, 0b1010)
+                    //We take the 0 first, then look at the next character to see if it's one of these prefixes
+                    if c == '0' {
                         chars.next();
-                    } else { //If we encounter something that isn’t a letter, digit, or underscore (like a space or punctuation)
-                        break; // we are ending loop
+                        num.push('0');
+                        if let Some(&(_, prefix @ ('x' | 'X' | 'b' | 'B'))) = chars.peek() {
+                            chars.next();
+                            let radix = if prefix == 'x' || prefix == 'X' { 16 } else { 2 };
+                            let mut digits = String::new();
+                            while let Some(&(_, c)) = chars.peek() {
+                                if c.is_ascii_alphanumeric() {
+                                    digits.push(c);
+                                    chars.next();
+                                } else {
+                                    break;
+                                }
+                            }
+                            //Every collected character has to be a digit of this radix, so 0xG or 0b2 are errors
+                            let literal = format!("0{}{}", prefix, digits);
+                            if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+                                return Err(TokenizeError::InvalidNumber { digits: literal, position });
+                            }
+                            let parsed = u64::from_str_radix(&digits, radix).map_err(|err| match err.kind() {
+                                IntErrorKind::PosOverflow => TokenizeError::IntegerTooLarge { digits: literal.clone(), position },
+                                _ => TokenizeError::InvalidNumber { digits: literal.clone(), position },
+                            })?;
+                            return Ok(Token::Number(parsed));
+                        }
                     }
+
+                    while let Some(&(_, c)) = chars.peek() { //We keep peeking and reading characters as long as they’re digits
+                        if c.is_ascii_digit() || c == '_' { //or underscores, which can separate digits like 1_000_000
+                            num.push(c);
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    //An underscore is only allowed between two digits, so 5_ and 5__0 are errors
+                    //(a leading underscore like _5 never gets here, it starts an identifier)
+                    if num.ends_with('_') || num.contains("__") {
+                        return Err(TokenizeError::InvalidNumber { digits: num.clone(), position });
+                    }
+                    num.retain(|c| c != '_');
+
+                    //A '.' followed by a digit starts the fractional part (1.5), an 'e' or 'E' starts the exponent (1e10, 2.5E-3).
+                    //With either of them the number is a float. A '.' without a digit after it isn't taken, it's a Token::Dot
+                    let mut is_float = false;
+                    if let Some(&(i, '.')) = chars.peek() {
+                        if input[i + 1..].starts_with(|c: char| c.is_ascii_digit()) {
+                            is_float = true;
+                            num.push('.');
+                            chars.next();
+                            while let Some(&(_, c)) = chars.peek() {
+                                if c.is_ascii_digit() {
+                                    num.push(c);
+                                    chars.next();
+                                } else {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    //The exponent can have a sign, but it needs at least one digit, so 1e or 2E+ are errors
+                    if let Some(&(_, e @ ('e' | 'E'))) = chars.peek() {
+                        is_float = true;
+                        num.push(e);
+                        chars.next();
+                        if let Some(&(_, sign @ ('+' | '-'))) = chars.peek() {
+                            num.push(sign);
+                            chars.next();
+                        }
+                        let exponent_start = num.len();
+                        while let Some(&(_, c)) = chars.peek() {
+                            if c.is_ascii_digit() {
+                                num.push(c);
+                                chars.next();
+                            } else {
+                                break;
+                            }
+                        }
+                        if num.len() == exponent_start {
+                            return Err(TokenizeError::InvalidNumber { digits: num.clone(), position });
+                        }
+                    }
+                    //Rust can parse this form of float directly. A number too big for f64 (like 1e999) would become
+                    //infinity, which isn't a value you can write in SQL, so it's an error too
+                    if is_float {
+                        return match num.parse::<f64>() {
+                            Ok(parsed) if parsed.is_finite() => Ok(Token::Float(parsed)),
+                            _ => Err(TokenizeError::InvalidNumber { digits: num.clone(), position }),
+                        };
+                    }
+
+                    //After collecting the digits, it converts the string (e.g. "123") into a number (u64)
+                    //Only digits were collected, so the usual reason for this to fail is a number bigger than u64::MAX,
+                    //which gets its own error so it isn't confused with malformed input
+                    let parsed = num.parse::<u64>().map_err(|err| match err.kind() {
+                        IntErrorKind::PosOverflow => TokenizeError::IntegerTooLarge { digits: num.clone(), position },
+                        _ => TokenizeError::InvalidNumber { digits: num.clone(), position },
+                    })?;
+                    return Ok(Token::Number(parsed));
                 }
-                //There are two options next:
-                //For example: we have ident string (select), it converts it to uppercase and checks
-                //if it's a keyword using the match_keyword function
-                if let Some(keyword) = match_keyword(&ident.to_uppercase()) {
-                    tokens.push(Token::Keyword(keyword));
+
+                //This block handles identifiers (e.g., variable names, function names) and keywords (e.g., SELECT, FROM, etc.) in the input.
+                //It checks if the current character is alphabetic (a letter) or an underscore (_)
+                c if c.is_ascii_alphabetic() || c == '_' => {
+                    let mut end = input.len(); //If the identifier goes until the end of the input, this is where it ends
+                    while let Some(&(i, c)) = chars.peek() { //The loop checks the next character and takes it into the identifier as long as it’s either
+                        if c.is_ascii_alphanumeric() || c == '_' { //it can be letter,digit or underscore
+                            chars.next();
+                        } else { //If we encounter something that isn’t a letter, digit, or underscore (like a space or punctuation)
+                            end = i; //the identifier ends right before it
+                            break; // we are ending loop
+                        }
+                    }
+                    let ident = &input[start..end]; //The identifier is just a slice of the input, nothing is copied
+                    //There are two options next:
+                    //For example: we have ident string (select), it converts it to uppercase and checks
+                    //if it's a keyword using the keyword_for function (which does this without allocating a new string)
+                    if let Some(keyword) = keyword_for(ident) {
+                        return Ok(Token::Keyword(keyword));
+                    }
+                    //If it's not a keyword, it’s treated as a regular identifier (like variable names or table names)
+                    //So Token::Identifier is added
+                    else {
+                        return Ok(Token::Identifier(Cow::Borrowed(ident)));
+                    }
                 }
-                //If it's not a keyword, it’s treated as a regular identifier (like variable names or table names)
-                //So Token::Identifier is added
-                else {
-                    tokens.push(Token::Identifier(ident));
+                //if we have invalid character that don't match none of these patterns
+                //This block will handle with this by adding invalid character to the tokens list as a Token::Invalid
+                c => {
+                    chars.next();
+                    return Ok(Token::Invalid(c));
                 }
             }
-            //if we have invalid character that don't match none of these patterns
-            //This block will handle with this by adding invalid character to the tokens list as a Token::Invalid
-            c => {
-                chars.next();
-                tokens.push(Token::Invalid(c));
-            }
         }
+        // This part shows that it is end of the input
+        self.token_start = input.len();
+        Ok(Token::Eof)
     }
-    // This part shows that it is end of the input
-    //Finally, it returns the list of tokens that were successfully created
-    tokens.push(Token::Eof);
-    Ok(tokens)
 }
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<Token<'a>, TokenizeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let token = self.next_token();
+        // Nothing comes after the end of the input, and we don't try to continue after an error either
+        if matches!(token, Ok(Token::Eof) | Err(_)) {
+            self.finished = true;
+        }
+        Some(token)
+    }
+}
+
+// The tokenizer works with byte offsets (that's what char_indices gives), but errors report the position in characters,
+// so that it is the same number a user would count in the query, even if it contains letters like é.
+// This is only done when an error happens, so it doesn't slow down tokenizing
+fn char_position(input: &str, byte_offset: usize) -> usize {
+    input[..byte_offset].chars().count()
+}
+
+// The length of the longest keyword we know (REFERENCES). Anything longer can't be a keyword,
+// so remember to update this when adding a longer one
+const MAX_KEYWORD_LEN: usize = 10;
+
+// Checks if an identifier is a keyword, ignoring its case.
+// Most identifiers are either already uppercase or obviously not keywords (too long, or not plain ASCII),
+// so for those we skip uppercasing. Otherwise the uppercase version is built in a small buffer on the stack
+pub(crate) fn keyword_for(ident: &str) -> Option<Keyword> {
+    if ident.len() > MAX_KEYWORD_LEN || !ident.is_ascii() {
+        return None;
+    }
+    if !ident.bytes().any(|b| b.is_ascii_lowercase()) {
+        return match_keyword(ident);
+    }
+    let mut upper = [0u8; MAX_KEYWORD_LEN];
+    let upper = &mut upper[..ident.len()];
+    upper.copy_from_slice(ident.as_bytes());
+    upper.make_ascii_uppercase();
+    // Uppercasing ASCII keeps it valid UTF-8, so this can't fail
+    std::str::from_utf8(upper).ok().and_then(match_keyword)
+}
+
 //This function takes a string s (a potential keyword) and tries to match it to a known keyword
 //If it matches one of the predefined keywords, it returns a Some(Keyword) with the corresponding Keyword enum
 // If it doesn't match any keyword,it returns None.
-fn match_keyword(s: &str) -> Option<Keyword> {
+pub(crate) fn match_keyword(s: &str) -> Option<Keyword> {
     match s { //string s to several possible patterns and executes the corresponding block when a match is found
         "SELECT" => Some(Keyword::Select),
         "CREATE" => Some(Keyword::Create),
@@ -149,10 +491,225 @@ fn match_keyword(s: &str) -> Option<Keyword> {
         "BOOL" => Some(Keyword::Bool),
         "VARCHAR" => Some(Keyword::Varchar),
         "NULL" => Some(Keyword::Null),
+        "UNIQUE" => Some(Keyword::Unique),
+        "FOREIGN" => Some(Keyword::Foreign),
+        "REFERENCES" => Some(Keyword::References),
+        "FLOAT" => Some(Keyword::Float),
+        "DOUBLE" => Some(Keyword::Double),
+        "DECIMAL" => Some(Keyword::Decimal),
+        "DATE" => Some(Keyword::Date),
+        "TIME" => Some(Keyword::Time),
+        "TIMESTAMP" => Some(Keyword::Timestamp),
+        "TEXT" => Some(Keyword::Text),
+        "CHAR" => Some(Keyword::Char),
+        "ALTER" => Some(Keyword::Alter),
+        "ADD" => Some(Keyword::Add),
+        "DROP" => Some(Keyword::Drop),
+        "COLUMN" => Some(Keyword::Column),
+        "AS" => Some(Keyword::As),
+        "NULLS" => Some(Keyword::Nulls),
+        "FIRST" => Some(Keyword::First),
+        "LAST" => Some(Keyword::Last),
+        "DISTINCT" => Some(Keyword::Distinct),
+        "EXISTS" => Some(Keyword::Exists),
+        "WITH" => Some(Keyword::With),
+        "IN" => Some(Keyword::In),
+        "LIMIT" => Some(Keyword::Limit),
+        "OFFSET" => Some(Keyword::Offset),
+        "CAST" => Some(Keyword::Cast),
+        "TRUNCATE" => Some(Keyword::Truncate),
+        "IF" => Some(Keyword::If),
+        "IS" => Some(Keyword::Is),
+        "UNKNOWN" => Some(Keyword::Unknown),
+        "INTERVAL" => Some(Keyword::Interval),
+        "SECOND" => Some(Keyword::Second),
+        "MINUTE" => Some(Keyword::Minute),
+        "HOUR" => Some(Keyword::Hour),
+        "DAY" => Some(Keyword::Day),
+        "MONTH" => Some(Keyword::Month),
+        "YEAR" => Some(Keyword::Year),
+        "ALL" => Some(Keyword::All),
+        "ANY" => Some(Keyword::Any),
+        "SOME" => Some(Keyword::Some),
+        "ON" => Some(Keyword::On),
+        "DELETE" => Some(Keyword::Delete),
+        "UPDATE" => Some(Keyword::Update),
+        "CASCADE" => Some(Keyword::Cascade),
+        "RESTRICT" => Some(Keyword::Restrict),
+        "SET" => Some(Keyword::Set),
+        "NO" => Some(Keyword::No),
+        "ACTION" => Some(Keyword::Action),
+        "DEFAULT" => Some(Keyword::Default),
+        "COLLATE" => Some(Keyword::Collate),
+        "CHARACTER" => Some(Keyword::Character),
+        "LIKE" => Some(Keyword::Like),
+        "ESCAPE" => Some(Keyword::Escape),
+        "GROUP" => Some(Keyword::Group),
+        "ROLLUP" => Some(Keyword::Rollup),
+        "OVER" => Some(Keyword::Over),
+        "PARTITION" => Some(Keyword::Partition),
+        "UNION" => Some(Keyword::Union),
+        "EXCEPT" => Some(Keyword::Except),
+        "INTERSECT" => Some(Keyword::Intersect),
+        "MINUS" => Some(Keyword::Minus),
+        "INDEX" => Some(Keyword::Index),
+        "GENERATED" => Some(Keyword::Generated),
+        "ALWAYS" => Some(Keyword::Always),
+        "STORED" => Some(Keyword::Stored),
+        "VIRTUAL" => Some(Keyword::Virtual),
+        "EXPLAIN" => Some(Keyword::Explain),
+        "ANALYZE" => Some(Keyword::Analyze),
+        "INSERT" => Some(Keyword::Insert),
+        "INTO" => Some(Keyword::Into),
+        "VALUES" => Some(Keyword::Values),
+        "RETURNING" => Some(Keyword::Returning),
         _ => None,
     }
 }
 
 //So, match_keyword takes a string and tries to match it against known keywords.
 //if it is one of these keywords (matches), it returns the corresponding Keyword
-// If it doesn’t match, it returns None, indicating it wasn’t a recognized keyword
\ No newline at end of file
+// If it doesn’t match, it returns None, indicating it wasn’t a recognized keyword
+#[cfg(test)]
+mod tests {
+    use super::{tokenize, tokenize_debug, tokenize_with_comments, tokenize_with_spans, Tokenizer};
+    use crate::token::{Keyword, Token};
+
+    // A small xorshift generator, so the inputs are random but the same on every run
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    // Every tokenizer function has to return tokens or an error for any input, never panic.
+    // The spans have to be on character boundaries, so slicing the input with them can't panic either
+    fn check(input: &str) {
+        let _ = tokenize(input);
+        let _ = tokenize_with_spans(input);
+        let _ = tokenize_with_comments(input);
+        if let Ok(tokens) = tokenize_debug(input) {
+            for (_, start, end) in tokens {
+                assert!(start <= end && input.get(start..end).is_some(), "bad range {}..{} in {:?}", start, end, input);
+            }
+        }
+    }
+
+    #[test]
+    fn random_bytes_never_panic() {
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+        for _ in 0..20_000 {
+            let bytes: Vec<u8> = (0..random.below(40)).map(|_| random.next() as u8).collect();
+            check(&String::from_utf8_lossy(&bytes));
+        }
+    }
+
+    #[test]
+    fn random_sql_characters_never_panic() {
+        // Characters that start or end tokens, or need care: quotes, comment markers, number parts and multi-byte characters
+        let alphabet: Vec<char> = "'\"`-/*().,;:?$@!=<>|+%0129eE.xNnaZ_ \t\r\n\0é€😀\u{feff}".chars().collect();
+        let mut random = Random(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..50_000 {
+            let input: String = (0..random.below(30)).map(|_| alphabet[random.below(alphabet.len())]).collect();
+            check(&input);
+        }
+    }
+
+    #[test]
+    fn unfinished_tokens_never_panic() {
+        for input in ["99999999999999999999999", "1e999999", "'abc", "\"abc", "`abc", "/* abc", "N'", "$", "1.2.3", "0x"] {
+            check(input);
+        }
+        assert!(tokenize("99999999999999999999999").is_err());
+        assert!(tokenize("'abc").is_err());
+        assert!(tokenize("/* abc").is_err());
+    }
+
+    #[test]
+    fn comments_between_tokens_are_skipped() {
+        let plain = tokenize("SELECT a, b FROM t WHERE a = 1;").unwrap();
+        assert_eq!(tokenize("SELECT /* first */ a, -- the rest\n b FROM/**/t WHERE a = 1; -- done").unwrap(), plain);
+        assert_eq!(tokenize("SELECT a,/* x */b FROM t WHERE a=1;--no space").unwrap(), plain);
+    }
+
+    #[test]
+    fn comment_at_the_end_without_newline() {
+        assert_eq!(tokenize("SELECT a FROM t -- no newline after this").unwrap(), tokenize("SELECT a FROM t").unwrap());
+        assert_eq!(tokenize("--").unwrap(), vec![Token::Eof]);
+        assert_eq!(tokenize_with_comments("a --end").unwrap(), vec![Token::Identifier("a".into()), Token::Comment("end".into()), Token::Eof]);
+    }
+
+    #[test]
+    fn double_dash_inside_a_string_is_not_a_comment() {
+        let tokens = tokenize("SELECT '--not a comment', \"--\" FROM t -- a comment\nWHERE b = '/* still a string */';").unwrap();
+        assert_eq!(tokens[1], Token::String("--not a comment".into()));
+        assert_eq!(tokens[3], Token::Identifier("--".into()));
+        assert_eq!(tokens[9], Token::String("/* still a string */".into()));
+        assert_eq!(tokens.len(), 12);
+    }
+
+    #[test]
+    fn n_prefix_only_makes_a_string_right_before_a_quote() {
+        assert_eq!(tokenize("N'abc'").unwrap(), vec![Token::String("abc".into()), Token::Eof]);
+        assert_eq!(tokenize("n'abc'").unwrap(), vec![Token::String("abc".into()), Token::Eof]);
+        assert_eq!(tokenize("Name").unwrap(), vec![Token::Identifier("Name".into()), Token::Eof]);
+        assert_eq!(tokenize("N").unwrap(), vec![Token::Identifier("N".into()), Token::Eof]);
+        assert_eq!(tokenize("N 'abc'").unwrap(), vec![Token::Identifier("N".into()), Token::String("abc".into()), Token::Eof]);
+        assert_eq!(tokenize("N\"abc\"").unwrap(), vec![Token::Identifier("N".into()), Token::Identifier("abc".into()), Token::Eof]);
+    }
+
+    #[test]
+    fn long_query_gives_the_same_tokens() {
+        // SELECT c0, 'v0', 0.5, c1, 'v1', 1.5, ... FROM t WHERE c0 >= 0 AND c1 >= 1 AND ...;
+        let count = 300;
+        let mut sql = String::from("SELECT ");
+        let mut expected = vec![Token::Keyword(Keyword::Select)];
+        for i in 0..count {
+            if i > 0 {
+                sql.push_str(", ");
+                expected.push(Token::Comma);
+            }
+            sql.push_str(&format!("c{}, 'v{}', {}.5", i, i, i));
+            expected.extend([
+                Token::Identifier(format!("c{}", i).into()),
+                Token::Comma,
+                Token::String(format!("v{}", i).into()),
+                Token::Comma,
+                Token::Float(i as f64 + 0.5),
+            ]);
+        }
+        sql.push_str(" FROM t WHERE ");
+        expected.extend([Token::Keyword(Keyword::From), Token::Identifier("t".into()), Token::Keyword(Keyword::Where)]);
+        for i in 0..count {
+            if i > 0 {
+                sql.push_str(" AND ");
+                expected.push(Token::Keyword(Keyword::And));
+            }
+            sql.push_str(&format!("c{} >= {}", i, i));
+            expected.extend([Token::Identifier(format!("c{}", i).into()), Token::GreaterThanOrEqual, Token::Number(i as u64)]);
+        }
+        sql.push(';');
+        expected.extend([Token::Semicolon, Token::Eof]);
+        assert!(sql.len() > 8 * 1024, "the query is only {} bytes", sql.len());
+
+        let tokens = tokenize(&sql).unwrap();
+        assert_eq!(tokens.len(), expected.len());
+        for (i, (token, expected)) in tokens.iter().zip(&expected).enumerate() {
+            assert_eq!(token, expected, "token {}", i);
+        }
+        // The tokens with spans and the lazy Tokenizer give the same tokens as tokenize
+        let spanned: Vec<Token> = tokenize_with_spans(&sql).unwrap().into_iter().map(|(token, _)| token).collect();
+        assert_eq!(spanned, expected);
+        let lazy: Vec<Token> = Tokenizer::new(&sql).collect::<Result<_, _>>().unwrap();
+        assert_eq!(lazy, expected);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::statement::{AlterTableAction, Constraint, Expression, Statement, TableColumn, TableConstraint};
+
+/// A way to go through the whole AST without writing the recursion again every time (for example to collect every column name used in a query).
+/// 1. `visit_statement` – Called for every statement, including the query inside `CREATE TABLE ... AS SELECT`, the statement after `EXPLAIN`, both queries of a `UNION` (and the other set operations), the queries of a `WITH` clause and subqueries like `EXISTS (SELECT ...)` and `IN (SELECT ...)`.
+/// 2. `visit_expression` – Called for every expression: the columns of a `SELECT`, the `WHERE` filter, `ORDER BY` items, the columns of `CREATE INDEX`, `CHECK` constraints, `DEFAULT` values, the expressions of generated columns, the values of `INSERT` and `UPDATE`, `RETURNING` lists and all of their sub-expressions.
+///
+/// Both methods by default just continue into the children (using `walk_statement` and `walk_expression`). An implementation overrides the methods it is interested in, and calls the matching `walk_` function if it still wants to visit the children:
+/// ```
+/// use rust_sql_parser::{parse, walk_expression, Expression, Visitor};
+///
+/// struct Identifiers(Vec<String>);
+///
+/// impl Visitor for Identifiers {
+///     fn visit_expression(&mut self, expr: &Expression) {
+///         if let Expression::Identifier(name) = expr {
+///             self.0.push(name.clone());
+///         }
+///         walk_expression(self, expr);
+///     }
+/// }
+///
+/// let statement = parse("SELECT name FROM users WHERE age > 18;").unwrap();
+/// let mut identifiers = Identifiers(Vec::new());
+/// identifiers.visit_statement(&statement);
+/// assert_eq!(identifiers.0, ["name", "age"]);
+/// ```
+pub trait Visitor {
+    fn visit_statement(&mut self, stmt: &Statement) {
+        walk_statement(self, stmt);
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        walk_expression(self, expr);
+    }
+}
+
+// Visits every expression (and the inner statement of CREATE TABLE ... AS) of a statement, in the order they are written
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Statement) {
+    match stmt {
+        Statement::Select { columns, from: _, r#where, orderby, ctes, limit, group_by, with_rollup: _ } => {
+            for cte in ctes {
+                visitor.visit_statement(&cte.query);
+            }
+            for column in columns {
+                visitor.visit_expression(column);
+            }
+            if let Some(expr) = r#where {
+                visitor.visit_expression(expr);
+            }
+            for expr in group_by {
+                visitor.visit_expression(expr);
+            }
+            for item in orderby {
+                visitor.visit_expression(&item.expr);
+            }
+            if let Some(limit) = limit {
+                visitor.visit_expression(&limit.count);
+                if let Some(offset) = &limit.offset {
+                    visitor.visit_expression(offset);
+                }
+            }
+        }
+        Statement::CreateTable { table_name: _, column_list, table_constraints, if_not_exists: _ } => {
+            for column in column_list {
+                walk_column(visitor, column);
+            }
+            for constraint in table_constraints {
+                if let TableConstraint::Check(expr) = constraint {
+                    visitor.visit_expression(expr);
+                }
+            }
+        }
+        Statement::AlterTable { table_name: _, action } => {
+            if let AlterTableAction::AddColumn(column) = action {
+                walk_column(visitor, column);
+            }
+        }
+        Statement::CreateTableAs { table_name: _, if_not_exists: _, query } => visitor.visit_statement(query),
+        Statement::Truncate { table_name: _ } => {}
+        Statement::CreateIndex { name: _, table: _, columns, unique: _ } => {
+            for item in columns {
+                visitor.visit_expression(&item.expr);
+            }
+        }
+        Statement::Explain { analyze: _, inner } => visitor.visit_statement(inner),
+        Statement::SetOperation { op: _, all: _, left, right } => {
+            visitor.visit_statement(left);
+            visitor.visit_statement(right);
+        }
+        Statement::Insert { table_name: _, columns: _, values, returning } => {
+            for expr in values.iter().flatten() {
+                visitor.visit_expression(expr);
+            }
+            for expr in returning {
+                visitor.visit_expression(expr);
+            }
+        }
+        Statement::Update { table_name: _, assignments, r#where, returning } => {
+            for assignment in assignments {
+                visitor.visit_expression(&assignment.value);
+            }
+            if let Some(expr) = r#where {
+                visitor.visit_expression(expr);
+            }
+            for expr in returning {
+                visitor.visit_expression(expr);
+            }
+        }
+        Statement::Delete { table_name: _, r#where, returning } => {
+            if let Some(expr) = r#where {
+                visitor.visit_expression(expr);
+            }
+            for expr in returning {
+                visitor.visit_expression(expr);
+            }
+        }
+    }
+}
+
+// Visits the direct sub-expressions of an expression. Literals and names don't have any
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expression) {
+    match expr {
+        Expression::BinaryOperation { left_operand, operator: _, right_operand } => {
+            visitor.visit_expression(left_operand);
+            visitor.visit_expression(right_operand);
+        }
+        Expression::UnaryOperation { operand, operator: _ } | Expression::Cast { expr: operand, target: _ } => {
+            visitor.visit_expression(operand)
+        }
+        Expression::Tuple(elements) | Expression::And(elements) | Expression::Or(elements) => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        }
+        Expression::FunctionCall { name: _, args, distinct: _, window } => {
+            for arg in args {
+                visitor.visit_expression(arg);
+            }
+            if let Some(window) = window {
+                for expr in &window.partition_by {
+                    visitor.visit_expression(expr);
+                }
+                for item in &window.order_by {
+                    visitor.visit_expression(&item.expr);
+                }
+            }
+        }
+        Expression::Exists { query, negated: _ } => visitor.visit_statement(query),
+        Expression::InList { expr, list, negated: _ } => {
+            visitor.visit_expression(expr);
+            for element in list {
+                visitor.visit_expression(element);
+            }
+        }
+        Expression::Is { expr, value, negated: _ } | Expression::Like { expr, pattern: value, negated: _, escape: _ } => {
+            visitor.visit_expression(expr);
+            visitor.visit_expression(value);
+        }
+        Expression::InSubquery { expr, query, negated: _ }
+        | Expression::QuantifiedComparison { expr, op: _, quantifier: _, query } => {
+            visitor.visit_expression(expr);
+            visitor.visit_statement(query);
+        }
+        Expression::Number(_)
+        | Expression::Float(_)
+        | Expression::Placeholder(_)
+        | Expression::Bool(_)
+        | Expression::Identifier(_)
+        | Expression::String(_)
+        | Expression::Null
+        | Expression::Default
+        | Expression::Unknown
+        | Expression::Interval { .. }
+        | Expression::TypedLiteral { .. }
+        | Expression::AllColumns
+        | Expression::QualifiedIdentifier { .. }
+        | Expression::QualifiedAllColumns(_) => {}
+    }
+}
+
+// Only CHECK constraints and generated columns contain expressions
+fn walk_column<V: Visitor + ?Sized>(visitor: &mut V, column: &TableColumn) {
+    for constraint in &column.constraints {
+        if let Constraint::Check(expr) | Constraint::Default(expr) | Constraint::Generated { expr, stored: _ } = constraint {
+            visitor.visit_expression(expr);
+        }
+    }
+}
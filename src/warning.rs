@@ -0,0 +1,67 @@
+use std::fmt::{Display, Formatter};
+use crate::statement::{AlterTableAction, Constraint, Statement, TableColumn, TableConstraint};
+
+/// Problems in a statement that are not errors (the statement is valid SQL), but most likely mistakes. They are only collected by `parse_with_warnings`, the normal `parse` doesn't look for them.
+/// 1. `DuplicateConstraint` – The same constraint is written twice on one column, like `a INT NOT NULL NOT NULL`. `constraint` is the repeated constraint written as SQL.
+/// 2. `RedundantNotNull` – A column is `NOT NULL` and also part of the primary key, which already can't contain `NULL`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    DuplicateConstraint { column: String, constraint: String },
+    RedundantNotNull { column: String },
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::DuplicateConstraint { column, constraint } => {
+                write!(f, "Constraint {} is written more than once on column {}", constraint, column)
+            }
+            Warning::RedundantNotNull { column } => {
+                write!(f, "NOT NULL on column {} is redundant, it is part of the primary key", column)
+            }
+        }
+    }
+}
+
+// Looks through an already parsed statement for the problems described in Warning.
+// Only column definitions can have them, so SELECT statements never get warnings
+pub(crate) fn collect_warnings(statement: &Statement) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    match statement {
+        Statement::CreateTable { column_list, table_constraints, .. } => {
+            // A PRIMARY KEY (a, b) item makes its columns NOT NULL just like a PRIMARY KEY written after the column
+            let table_key: Vec<&String> = table_constraints.iter()
+                .filter_map(|constraint| match constraint {
+                    TableConstraint::PrimaryKey(columns) => Some(columns),
+                    _ => None,
+                })
+                .flatten()
+                .collect();
+            for column in column_list {
+                check_column(column, table_key.contains(&&column.column_name), &mut warnings);
+            }
+        }
+        Statement::AlterTable { action: AlterTableAction::AddColumn(column), .. } => check_column(column, false, &mut warnings),
+        Statement::Explain { inner, .. } => return collect_warnings(inner),
+        _ => {}
+    }
+    warnings
+}
+
+fn check_column(column: &TableColumn, in_table_key: bool, warnings: &mut Vec<Warning>) {
+    for (i, constraint) in column.constraints.iter().enumerate() {
+        // Only the second appearance is reported, so a constraint written three times gives one warning
+        let second = column.constraints[..i].iter().filter(|other| *other == constraint).count() == 1;
+        if second {
+            warnings.push(Warning::DuplicateConstraint {
+                column: column.column_name.clone(),
+                constraint: constraint.to_string(),
+            });
+        }
+    }
+    let not_null = column.constraints.contains(&Constraint::NotNull);
+    let primary_key = in_table_key || column.constraints.contains(&Constraint::PrimaryKey);
+    if not_null && primary_key {
+        warnings.push(Warning::RedundantNotNull { column: column.column_name.clone() });
+    }
+}